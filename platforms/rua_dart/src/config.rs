@@ -16,6 +16,23 @@ pub struct RuaConfig {
 struct RuaConfigData {
     native_entry: Option<String>,
     platform_entry: Option<String>,
+    symbol_prefix: Option<String>,
+    library_path: Option<String>,
+    dart_ffi_lookup: Option<DartFfiLookup>,
+}
+
+/// When the generated Dart code looks up the native functions.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum DartFfiLookup {
+    /// On the first call of each function, which keeps the startup cheap.
+    #[default]
+    Lazy,
+    /// All at once when `lookupFunctions` is called, which surfaces missing
+    /// symbols early.
+    Eager,
 }
 
 #[derive(Debug)]
@@ -91,6 +108,9 @@ impl RuaConfig {
                         platform_entry: Some(
                             DEFAULT_PLATFORM_ENTRY.to_string(),
                         ),
+                        symbol_prefix: None,
+                        library_path: None,
+                        dart_ffi_lookup: None,
                     },
                 }
             }
@@ -123,6 +143,24 @@ impl RuaConfig {
             .unwrap_or(DEFAULT_PLATFORM_ENTRY)
     }
 
+    /// Returns the prefix of the native symbol names, e.g. `mycrate` for
+    /// `mycrate_module_fn`, if any.
+    pub fn get_symbol_prefix(&self) -> Option<&str> {
+        self.data.symbol_prefix.as_deref()
+    }
+
+    /// Returns the path the generated code opens the native library from,
+    /// e.g. `libmycrate.so`, if any. Without it, the symbols are looked up
+    /// in the process, where the library must already be loaded.
+    pub fn get_library_path(&self) -> Option<&str> {
+        self.data.library_path.as_deref()
+    }
+
+    /// Returns when the generated code looks up the native functions.
+    pub fn get_dart_ffi_lookup(&self) -> DartFfiLookup {
+        self.data.dart_ffi_lookup.unwrap_or_default()
+    }
+
     pub fn set_native_entry(&mut self, entry: &str) {
         self.data.native_entry = Some(entry.to_string());
     }
@@ -130,4 +168,16 @@ impl RuaConfig {
     pub fn set_platform_entry(&mut self, entry: &str) {
         self.data.platform_entry = Some(entry.to_string());
     }
+
+    pub fn set_symbol_prefix(&mut self, prefix: &str) {
+        self.data.symbol_prefix = Some(prefix.to_string());
+    }
+
+    pub fn set_library_path(&mut self, path: &str) {
+        self.data.library_path = Some(path.to_string());
+    }
+
+    pub fn set_dart_ffi_lookup(&mut self, lookup: DartFfiLookup) {
+        self.data.dart_ffi_lookup = Some(lookup);
+    }
 }
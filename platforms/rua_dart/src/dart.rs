@@ -1,48 +1,389 @@
+use std::{fmt::Display, io::Write};
+
 use rua_gen::{
-    models::{Rua, RuaMod},
-    utils::RuaCaseConverter,
+    logic::Rua,
+    models::{
+        RuaCase, RuaEnum, RuaFn, RuaMod, RuaNamed, RuaSigFn, RuaStruct, RuaType,
+    },
+    render::{render_type, RuaTypeRenderer},
 };
 
-use crate::config::RuaConfig;
+use crate::config::{DartFfiLookup, RuaConfig};
 
 #[derive(Debug)]
 pub struct RuaDart {
     config: RuaConfig,
     type_defs: Vec<String>,
     fn_defs: Vec<String>,
+    /// The statements looking up the native functions, called at startup
+    /// with [DartFfiLookup::Eager].
+    lookups: Vec<String>,
     class_defs: Vec<String>,
     enum_defs: Vec<String>,
 }
 
+impl RuaDart {
+    pub fn new(config: RuaConfig) -> Self {
+        Self {
+            config,
+            type_defs: Vec::new(),
+            fn_defs: Vec::new(),
+            lookups: Vec::new(),
+            class_defs: Vec::new(),
+            enum_defs: Vec::new(),
+        }
+    }
+
+    /// Writes the generated code to the writer instead of the files, e.g. a
+    /// `Vec<u8>` in tests or a file in `OUT_DIR` in build scripts. The
+    /// generated code can also be taken as a string with `to_string`.
+    pub fn emit_to(&self, w: &mut impl Write) -> std::io::Result<()> {
+        write!(w, "{}", self)
+    }
+
+    /// Returns the name of the native symbol of the function, i.e.
+    /// `module_fn` prefixed with the symbol prefix if any, e.g.
+    /// `mycrate_users_get_user`.
+    fn native_symbol_name(&self, m: &RuaMod, f: &RuaSigFn) -> String {
+        let name = native_type_name(m, f);
+        match self.config.get_symbol_prefix() {
+            Some(prefix) => format!("{}_{}", prefix, name),
+            None => name,
+        }
+    }
+
+    /// Returns the expression opening the native library.
+    fn library(&self) -> String {
+        match self.config.get_library_path() {
+            Some(path) => format!("DynamicLibrary.open('{}')", path),
+            None => "DynamicLibrary.process()".to_owned(),
+        }
+    }
+}
+
+/// Returns the name of the function in the native library without the
+/// symbol prefix, i.e. `module_fn`, which the Dart names are derived from.
+fn native_type_name(m: &RuaMod, f: &RuaSigFn) -> String {
+    format!(
+        "{}_{}",
+        m.name().get_name_with_case(&RuaCase::SnakeCase),
+        f.name().get_name_with_case(&RuaCase::SnakeCase)
+    )
+}
+
+/// Checks if the type can cross the FFI boundary, i.e. is a number, a bool,
+/// `()` or a pointer to one of them.
+fn is_ffi_type(ty: &RuaType) -> bool {
+    match ty {
+        RuaType::I8
+        | RuaType::I16
+        | RuaType::I32
+        | RuaType::I64
+        | RuaType::Isize
+        | RuaType::U8
+        | RuaType::U16
+        | RuaType::U32
+        | RuaType::U64
+        | RuaType::Usize
+        | RuaType::F32
+        | RuaType::F64
+        | RuaType::Bool
+        | RuaType::Unit => true,
+        RuaType::Pointer(ptr) => is_ffi_type(&ptr.ty),
+        _ => false,
+    }
+}
+
+/// Renders the types of the native signatures, e.g. `Int32` for `i32`.
+struct DartNativeTypes;
+
+impl RuaTypeRenderer for DartNativeTypes {
+    fn render_leaf(&self, ty: &RuaType) -> String {
+        let name = match ty {
+            RuaType::I8 => "Int8",
+            RuaType::I16 => "Int16",
+            RuaType::I32 => "Int32",
+            RuaType::I64 => "Int64",
+            RuaType::Isize => "IntPtr",
+            RuaType::U8 => "Uint8",
+            RuaType::U16 => "Uint16",
+            RuaType::U32 => "Uint32",
+            RuaType::U64 => "Uint64",
+            RuaType::Usize => "Size",
+            RuaType::F32 => "Float",
+            RuaType::F64 => "Double",
+            RuaType::Bool => "Bool",
+            RuaType::Unit => "Void",
+            ty => panic!("{} is not an FFI type, see Rua::check_supported", ty),
+        };
+        name.to_owned()
+    }
+
+    fn render_pointer(&self, _is_const: bool, elem: String) -> String {
+        format!("Pointer<{}>", elem)
+    }
+
+    fn render_non_null(&self, elem: String) -> String {
+        format!("Pointer<{}>", elem)
+    }
+}
+
+/// Renders the types of the Dart signatures, e.g. `int` for `i32`.
+struct DartTypes;
+
+impl RuaTypeRenderer for DartTypes {
+    fn render_leaf(&self, ty: &RuaType) -> String {
+        let name = match ty {
+            RuaType::I8
+            | RuaType::I16
+            | RuaType::I32
+            | RuaType::I64
+            | RuaType::Isize
+            | RuaType::U8
+            | RuaType::U16
+            | RuaType::U32
+            | RuaType::U64
+            | RuaType::Usize => "int",
+            RuaType::F32 | RuaType::F64 => "double",
+            RuaType::Bool => "bool",
+            RuaType::Unit => "void",
+            ty => panic!("{} is not an FFI type, see Rua::check_supported", ty),
+        };
+        name.to_owned()
+    }
+
+    fn render(&self, ty: &RuaType) -> String {
+        match ty {
+            // pointers keep their native type in Dart
+            RuaType::Pointer(_) => DartNativeTypes.render(ty),
+            _ => render_type(self, ty),
+        }
+    }
+}
+
+impl Display for RuaDart {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.file_header())?;
+        write!(f, "\nimport 'dart:ffi';\n")?;
+        write!(f, "\nfinal DynamicLibrary _lib = {};\n", self.library())?;
+        let defs = [
+            &self.type_defs,
+            &self.fn_defs,
+            &self.class_defs,
+            &self.enum_defs,
+        ];
+        for def in defs.into_iter().flatten() {
+            write!(f, "\n{}\n", def)?;
+        }
+        if !self.lookups.is_empty() {
+            write!(
+                f,
+                "\n/// Looks up all the native functions, so that a missing \
+                 one fails here\n/// rather than on its first call.\n\
+                 void lookupFunctions() {{\n"
+            )?;
+            for lookup in self.lookups.iter() {
+                writeln!(f, "  {}", lookup)?;
+            }
+            writeln!(f, "}}")?;
+        }
+        Ok(())
+    }
+}
+
 impl Rua for RuaDart {
     fn entry_path(&self) -> std::path::PathBuf {
         self.config.get_native_entry().into()
     }
 
-    fn write_fn<T: rua_gen::models::RuaFn>(
-        &mut self,
-        m: &rua_gen::logic::Module,
-        f: &T,
-    ) {
-        let native_type_name =
-            format!("{}_{}", m.name.to_snake_case(), f.name().to_snake_case());
-        let dart_type_name = native_type_name.to_pascal_case();
-        let dart_name = native_type_name.to_camel_case();
+    fn supports(&self, ty: &RuaType) -> bool {
+        is_ffi_type(ty)
+    }
+
+    fn write_fn(&mut self, m: &RuaMod, f: &RuaFn) {
+        let f = match f {
+            RuaFn::Fn(f) => f,
+            // bare function types have no symbol to look up
+            RuaFn::Bare(_) => return,
+        };
+        let native_type_name = native_type_name(m, f);
+        // the prefix only avoids collisions between native libraries, so it
+        // is kept out of the Dart names
+        let native_symbol_name = self.native_symbol_name(m, f);
+        let dart_type_name = RuaCase::PascalCase.convert(&native_type_name);
+        let dart_name = RuaCase::CamelCase.convert(&native_type_name);
+        let params = |renderer: &dyn RuaTypeRenderer| {
+            f.params
+                .iter()
+                .map(|param| renderer.render(&param.ty))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        self.type_defs.push(format!(
+            "typedef {}Native = {} Function({});",
+            dart_type_name,
+            DartNativeTypes.render(&f.ret),
+            params(&DartNativeTypes)
+        ));
+        self.type_defs.push(format!(
+            "typedef {} = {} Function({});",
+            dart_type_name,
+            DartTypes.render(&f.ret),
+            params(&DartTypes)
+        ));
+        let lookup = format!(
+            "_lib.lookupFunction<{}Native, {}>('{}')",
+            dart_type_name, dart_type_name, native_symbol_name
+        );
+        match self.config.get_dart_ffi_lookup() {
+            // top-level variables are initialized on their first access
+            DartFfiLookup::Lazy => self.fn_defs.push(format!(
+                "final {} {} = {};",
+                dart_type_name, dart_name, lookup
+            )),
+            DartFfiLookup::Eager => {
+                self.fn_defs.push(format!(
+                    "late final {} {};",
+                    dart_type_name, dart_name
+                ));
+                self.lookups.push(format!("{} = {};", dart_name, lookup));
+            }
+        }
     }
 
-    fn write_struct<T: rua_gen::models::RuaStruct>(
-        &mut self,
-        m: &rua_gen::logic::Module,
-        s: &T,
-    ) {
+    fn write_struct(&mut self, _m: &RuaMod, _s: &RuaStruct) {
         todo!()
     }
 
-    fn write_enum<T: rua_gen::models::RuaEnum>(
-        &mut self,
-        m: &rua_gen::logic::Module,
-        e: &T,
-    ) {
+    fn write_enum(&mut self, _m: &RuaMod, _e: &RuaEnum) {
         todo!()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use rua_gen::models::{RuaModType, RuaName, RuaPointer, RuaVar};
+
+    use super::*;
+
+    fn users() -> RuaMod {
+        RuaMod::new("users", RuaModType::FileModule, None, true)
+    }
+
+    fn param(name: &str, ty: RuaType) -> RuaVar {
+        RuaVar {
+            name: RuaName::new(name, RuaCase::SnakeCase),
+            ty: Box::new(ty),
+            default: None,
+        }
+    }
+
+    fn get_user() -> RuaSigFn {
+        RuaSigFn {
+            name: RuaName::new("get_user", RuaCase::SnakeCase),
+            generics: Vec::new(),
+            receiver: None,
+            params: vec![
+                param("id", RuaType::U64),
+                param(
+                    "out",
+                    RuaType::Pointer(RuaPointer {
+                        is_const: false,
+                        is_non_null: false,
+                        ty: Box::new(RuaType::U8),
+                    }),
+                ),
+            ],
+            ret: Box::new(RuaType::Bool),
+        }
+    }
+
+    fn write_get_user(config: RuaConfig) -> RuaDart {
+        let mut dart = RuaDart::new(config);
+        dart.write_fn(&users(), &RuaFn::Fn(get_user()));
+        dart
+    }
+
+    #[test]
+    fn symbol_prefix_is_in_the_native_symbol_name() {
+        let mut config = RuaConfig::default();
+        config.set_symbol_prefix("mycrate");
+        let dart = RuaDart::new(config);
+        let name = dart.native_symbol_name(&users(), &get_user());
+        assert_eq!(name, "mycrate_users_get_user");
+    }
+
+    #[test]
+    fn native_symbol_name_is_unprefixed_by_default() {
+        let dart = RuaDart::new(RuaConfig::default());
+        let name = dart.native_symbol_name(&users(), &get_user());
+        assert_eq!(name, "users_get_user");
+    }
+
+    #[test]
+    fn write_fn_declares_the_library_typedefs_and_lookup() {
+        let mut config = RuaConfig::default();
+        config.set_symbol_prefix("mycrate");
+        config.set_library_path("libmycrate.so");
+        let out = write_get_user(config).to_string();
+        for line in [
+            "import 'dart:ffi';",
+            "final DynamicLibrary _lib = \
+             DynamicLibrary.open('libmycrate.so');",
+            "typedef UsersGetUserNative = \
+             Bool Function(Uint64, Pointer<Uint8>);",
+            "typedef UsersGetUser = bool Function(int, Pointer<Uint8>);",
+            "final UsersGetUser usersGetUser = _lib.lookupFunction\
+             <UsersGetUserNative, UsersGetUser>('mycrate_users_get_user');",
+        ] {
+            assert!(out.contains(line), "{:?} is not in:\n{}", line, out);
+        }
+    }
+
+    #[test]
+    fn lazy_lookup_initializes_the_function_on_first_access() {
+        let mut config = RuaConfig::default();
+        config.set_dart_ffi_lookup(DartFfiLookup::Lazy);
+        let dart = write_get_user(config);
+        assert_eq!(
+            dart.fn_defs,
+            ["final UsersGetUser usersGetUser = _lib.lookupFunction\
+              <UsersGetUserNative, UsersGetUser>('users_get_user');"]
+        );
+        assert!(!dart.to_string().contains("lookupFunctions()"));
+    }
+
+    #[test]
+    fn eager_lookup_looks_up_all_functions_at_once() {
+        let mut config = RuaConfig::default();
+        config.set_dart_ffi_lookup(DartFfiLookup::Eager);
+        let dart = write_get_user(config);
+        assert_eq!(dart.fn_defs, ["late final UsersGetUser usersGetUser;"]);
+        let out = dart.to_string();
+        let expected = "void lookupFunctions() {\n  \
+                        usersGetUser = _lib.lookupFunction\
+                        <UsersGetUserNative, UsersGetUser>('users_get_user');\n\
+                        }\n";
+        assert!(out.ends_with(expected), "{}", out);
+    }
+
+    #[test]
+    fn only_ffi_types_are_supported() {
+        let dart = RuaDart::new(RuaConfig::default());
+        assert!(dart.supports(&RuaType::F32));
+        assert!(!dart.supports(&RuaType::String));
+        assert!(!dart.supports(&RuaType::U128));
+    }
+
+    #[test]
+    fn emit_to_writes_the_generated_code_to_a_buffer() {
+        let dart = write_get_user(RuaConfig::default());
+        let mut buf = Vec::new();
+        dart.emit_to(&mut buf).unwrap();
+        let out = String::from_utf8(buf).unwrap();
+        assert!(out.starts_with("// GENERATED CODE - DO NOT MODIFY BY HAND\n"));
+        assert!(out.contains("DynamicLibrary.process()"));
+        assert!(out.contains(&dart.fn_defs[0]));
+        assert_eq!(out, dart.to_string());
+    }
+}
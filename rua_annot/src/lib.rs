@@ -9,7 +9,11 @@
 //! the code in Rust compile in the C ABI, making it possible in the FFI.
 #![warn(clippy::all, missing_docs)]
 use proc_macro2::TokenStream;
-use syn::{parse_macro_input, Item, ItemEnum, ItemFn, ItemStruct};
+use syn::{
+    parse_macro_input, punctuated::Punctuated, Attribute, Fields, ImplItem,
+    Item, ItemEnum, ItemFn, ItemImpl, ItemStruct, ItemTrait, Meta, Token,
+    TraitItem, TraitItemFn,
+};
 
 extern crate proc_macro;
 
@@ -17,26 +21,69 @@ extern crate proc_macro;
 /// - If applied to a function, say `fn foo() -> i32`, it will make the
 ///   function `#[no_mangle] pub extern "C" fn foo() -> i32`.
 /// - If applied to a struct or enum, it will make the struct or enum
-///   `#[repr(C)]`.
+///   `#[repr(C)]`, or `#[repr(transparent)]` for a newtype struct marked
+///   `#[rua(transparent)]`.
+/// - If applied to a trait or an impl block, it will only strip the
+///   attributes described below.
 /// - If applied to anything else, it will panic.
+///
+/// The `#[rua(...)]` attributes on fields, enum variants and the methods of
+/// traits and impl blocks, e.g. `#[rua(default = "0")]` or
+/// `#[rua(monomorphize(T = "u32"))]`, are only read by the generator and are
+/// removed from the output. A method annotated on its own, outside of a
+/// `#[rua]` trait or impl block, is likewise left as is.
 #[proc_macro_attribute]
 pub fn rua(
-    _attr: proc_macro::TokenStream,
+    attr: proc_macro::TokenStream,
     item: proc_macro::TokenStream,
 ) -> proc_macro::TokenStream {
+    let args = parse_macro_input!(
+        attr with Punctuated::<Meta, Token![,]>::parse_terminated
+    );
     let item = parse_macro_input!(item as Item);
-    handle_item(&item).into()
+    handle_item(&args, &item).into()
 }
 
-fn handle_item(item: &Item) -> TokenStream {
+fn handle_item(args: &Punctuated<Meta, Token![,]>, item: &Item) -> TokenStream {
     match item {
+        Item::Fn(f) if is_method(args, f) => {
+            let mut f = f.clone();
+            strip_rua_attrs(&mut f.attrs);
+            quote::quote!(#f)
+        }
         Item::Fn(f) => handle_item_fn(f),
-        Item::Struct(s) => handle_item_struct(s),
+        Item::Struct(s) => handle_item_struct(
+            s,
+            args.iter().any(|arg| arg.path().is_ident("transparent")),
+        ),
         Item::Enum(e) => handle_item_enum(e),
-        _ => panic!("flusty can only be used on functions"),
+        Item::Trait(t) => handle_item_trait(t),
+        Item::Impl(i) => handle_item_impl(i),
+        // A bodyless trait method only carries hints for the generator.
+        Item::Verbatim(tokens) => {
+            match syn::parse2::<TraitItemFn>(tokens.clone()) {
+                Ok(mut f) => {
+                    strip_rua_attrs(&mut f.attrs);
+                    quote::quote!(#f)
+                }
+                Err(err) => err.to_compile_error(),
+            }
+        }
+        _ => panic!(
+            "rua can only be used on functions, structs, enums, traits and \
+             impl blocks"
+        ),
     }
 }
 
+/// Whether `f` is a method annotated on its own rather than a free function:
+/// it takes `self`, or it carries monomorphization hints, which only generic
+/// methods need.
+fn is_method(args: &Punctuated<Meta, Token![,]>, f: &ItemFn) -> bool {
+    f.sig.receiver().is_some()
+        || args.iter().any(|arg| arg.path().is_ident("monomorphize"))
+}
+
 fn handle_item_fn(f: &ItemFn) -> TokenStream {
     let sig = &f.sig;
     let body = &f.block;
@@ -48,7 +95,15 @@ fn handle_item_fn(f: &ItemFn) -> TokenStream {
     }
 }
 
-fn handle_item_struct(s: &ItemStruct) -> TokenStream {
+fn handle_item_struct(s: &ItemStruct, is_transparent: bool) -> TokenStream {
+    let mut s = s.clone();
+    strip_field_attrs(&mut s.fields);
+    if is_transparent {
+        return quote::quote! {
+            #[repr(transparent)]
+            #s
+        };
+    }
     quote::quote! {
         #[repr(C)]
         #s
@@ -56,8 +111,43 @@ fn handle_item_struct(s: &ItemStruct) -> TokenStream {
 }
 
 fn handle_item_enum(e: &ItemEnum) -> TokenStream {
+    let mut e = e.clone();
+    for variant in e.variants.iter_mut() {
+        strip_rua_attrs(&mut variant.attrs);
+        strip_field_attrs(&mut variant.fields);
+    }
     quote::quote! {
         #[repr(C)]
         #e
     }
 }
+
+fn handle_item_trait(t: &ItemTrait) -> TokenStream {
+    let mut t = t.clone();
+    for item in t.items.iter_mut() {
+        if let TraitItem::Fn(f) = item {
+            strip_rua_attrs(&mut f.attrs);
+        }
+    }
+    quote::quote!(#t)
+}
+
+fn handle_item_impl(i: &ItemImpl) -> TokenStream {
+    let mut i = i.clone();
+    for item in i.items.iter_mut() {
+        if let ImplItem::Fn(f) = item {
+            strip_rua_attrs(&mut f.attrs);
+        }
+    }
+    quote::quote!(#i)
+}
+
+fn strip_field_attrs(fields: &mut Fields) {
+    for field in fields.iter_mut() {
+        strip_rua_attrs(&mut field.attrs);
+    }
+}
+
+fn strip_rua_attrs(attrs: &mut Vec<Attribute>) {
+    attrs.retain(|attr| !attr.path().is_ident("rua"));
+}
@@ -0,0 +1,491 @@
+//! This module contains the evaluation of `#[cfg(...)]` attributes.
+use std::collections::HashSet;
+
+use syn::{
+    punctuated::Punctuated, spanned::Spanned, Attribute, Expr, Item, Lit, Meta,
+    Token,
+};
+
+use crate::errors::ConversionError;
+
+/// The set of active configuration options, used to decide whether items
+/// gated behind `#[cfg(...)]` attributes should be generated.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RuaCfg {
+    /// The active options without a value, e.g. `unix`.
+    flags: HashSet<String>,
+    /// The active options with a value, e.g. `feature = "serde"`.
+    values: HashSet<(String, String)>,
+    /// The target triple set with [RuaCfg::set_target], if any.
+    target: Option<String>,
+}
+
+impl RuaCfg {
+    /// Creates a configuration where no option is active.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Activates an option without a value, e.g. `unix`.
+    pub fn enable_flag(&mut self, name: impl AsRef<str>) -> &mut Self {
+        self.flags.insert(name.as_ref().to_owned());
+        self
+    }
+
+    /// Activates an option with a value, e.g. `target_os = "linux"`.
+    pub fn enable_value(
+        &mut self,
+        key: impl AsRef<str>,
+        value: impl AsRef<str>,
+    ) -> &mut Self {
+        self.values
+            .insert((key.as_ref().to_owned(), value.as_ref().to_owned()));
+        self
+    }
+
+    /// Activates a feature, i.e. `feature = "name"`.
+    pub fn enable_feature(&mut self, name: impl AsRef<str>) -> &mut Self {
+        self.enable_value("feature", name)
+    }
+
+    /// Sets the target triple, e.g. `aarch64-apple-ios`, and activates the
+    /// options derived from it: `target_arch`, `target_vendor`, `target_os`,
+    /// `target_env`, `target_abi`, `target_pointer_width`, `target_endian`,
+    /// `target_family` if any, and `unix` or `windows`. This lets the same
+    /// crate drive the outputs of several platforms. The options of a
+    /// previously set target are not deactivated.
+    pub fn set_target(&mut self, triple: impl AsRef<str>) -> &mut Self {
+        let triple = triple.as_ref();
+        let target = Target::parse(triple);
+        self.enable_value("target_arch", target.arch)
+            .enable_value("target_vendor", target.vendor)
+            .enable_value("target_os", target.os)
+            .enable_value("target_env", target.env)
+            .enable_value("target_abi", target.abi)
+            .enable_value("target_pointer_width", target.pointer_width)
+            .enable_value("target_endian", target.endian);
+        match target.family {
+            Some(family @ ("unix" | "windows")) => {
+                self.enable_value("target_family", family)
+                    .enable_flag(family);
+            }
+            Some(family) => {
+                self.enable_value("target_family", family);
+            }
+            None => {}
+        }
+        self.target = Some(triple.to_owned());
+        self
+    }
+
+    /// Returns the target triple set with [RuaCfg::set_target], if any.
+    pub fn target(&self) -> Option<&str> {
+        self.target.as_deref()
+    }
+
+    /// Checks if an item with the attributes is enabled, i.e. all of its
+    /// `#[cfg(...)]` attributes hold. Other attributes are ignored.
+    pub fn is_enabled(
+        &self,
+        attrs: &[Attribute],
+    ) -> Result<bool, ConversionError> {
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("cfg")) {
+            let pred = attr.parse_args::<Meta>().map_err(|err| {
+                ConversionError::builder()
+                    .span(&err.span())
+                    .source_type("syn::Attribute")
+                    .target_type("RuaCfg")
+                    .message(format!("invalid cfg predicate: {}", err))
+                    .build()
+            })?;
+            if !self.eval(&pred)? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Checks if the item is enabled, see [RuaCfg::is_enabled].
+    pub fn is_item_enabled(
+        &self,
+        item: &Item,
+    ) -> Result<bool, ConversionError> {
+        self.is_enabled(item_attrs(item))
+    }
+
+    /// Evaluates a cfg predicate, i.e. the content of `#[cfg(...)]`.
+    pub fn eval(&self, pred: &Meta) -> Result<bool, ConversionError> {
+        let generate_error = |msg: &str| {
+            Err(ConversionError::builder()
+                .span(&pred.span())
+                .source_type("syn::Meta")
+                .target_type("RuaCfg")
+                .message(msg)
+                .build())
+        };
+        match pred {
+            Meta::Path(path) => match path.get_ident() {
+                Some(ident) => Ok(self.flags.contains(&ident.to_string())),
+                None => generate_error("cfg option must be an identifier"),
+            },
+            Meta::NameValue(name_value) => {
+                let key = match name_value.path.get_ident() {
+                    Some(ident) => ident.to_string(),
+                    None => {
+                        return generate_error(
+                            "cfg option must be an identifier",
+                        )
+                    }
+                };
+                let value = match &name_value.value {
+                    Expr::Lit(lit) => match &lit.lit {
+                        Lit::Str(s) => s.value(),
+                        _ => {
+                            return generate_error(
+                                "cfg value must be a string literal",
+                            )
+                        }
+                    },
+                    _ => {
+                        return generate_error(
+                            "cfg value must be a string literal",
+                        )
+                    }
+                };
+                Ok(self.values.contains(&(key, value)))
+            }
+            Meta::List(list) => {
+                let args = list
+                    .parse_args_with(
+                        Punctuated::<Meta, Token![,]>::parse_terminated,
+                    )
+                    .map_err(|err| {
+                        ConversionError::builder()
+                            .span(&err.span())
+                            .source_type("syn::Meta")
+                            .target_type("RuaCfg")
+                            .message(format!("invalid cfg predicate: {}", err))
+                            .build()
+                    })?;
+                if list.path.is_ident("all") {
+                    for arg in args.iter() {
+                        if !self.eval(arg)? {
+                            return Ok(false);
+                        }
+                    }
+                    Ok(true)
+                } else if list.path.is_ident("any") {
+                    for arg in args.iter() {
+                        if self.eval(arg)? {
+                            return Ok(true);
+                        }
+                    }
+                    Ok(false)
+                } else if list.path.is_ident("not") {
+                    if args.len() != 1 {
+                        return generate_error(
+                            "not() takes exactly one cfg predicate",
+                        );
+                    }
+                    Ok(!self.eval(&args[0])?)
+                } else {
+                    generate_error(
+                        "unsupported cfg operator, expected all, any or not",
+                    )
+                }
+            }
+        }
+    }
+}
+
+/// The cfg options of a target triple, see [RuaCfg::set_target]. The env and
+/// the abi are empty if the triple has none, as with `rustc`.
+struct Target<'a> {
+    arch: &'a str,
+    vendor: &'a str,
+    os: &'a str,
+    env: &'a str,
+    abi: &'a str,
+    family: Option<&'a str>,
+    pointer_width: &'a str,
+    endian: &'a str,
+}
+
+impl<'a> Target<'a> {
+    /// Parses a triple of the form `<arch>-<vendor>-<os>[-<env><abi>]`, where
+    /// the vendor may be omitted, e.g. `aarch64-linux-android`, and so may
+    /// the os of bare-metal targets, e.g. `thumbv7em-none-eabihf`.
+    fn parse(triple: &'a str) -> Self {
+        let parts = triple.split('-').collect::<Vec<_>>();
+        let raw_arch = parts[0];
+        let (vendor, os, env_abi) = match &parts[1..] {
+            ["none", abi] => ("unknown", "none", *abi),
+            ["linux", env_abi] => ("unknown", "linux", *env_abi),
+            [vendor, os] => (*vendor, *os, ""),
+            [vendor, os, env_abi, ..] => (*vendor, *os, *env_abi),
+            [os] => ("unknown", *os, ""),
+            [] => ("unknown", "none", ""),
+        };
+        let (os, env, abi) = match os {
+            "darwin" => ("macos", "", env_abi),
+            "linux" if env_abi.starts_with("android") => {
+                ("android", "", &env_abi["android".len()..])
+            }
+            // e.g. `wasm32-wasip1`
+            os if os.starts_with("wasi") => ("wasi", &os["wasi".len()..], ""),
+            os => {
+                let (env, abi) = split_env_abi(env_abi);
+                (os, env, abi)
+            }
+        };
+        let arch = normalize_arch(raw_arch);
+        let family = if os == "windows" {
+            Some("windows")
+        } else if arch.starts_with("wasm") {
+            Some("wasm")
+        } else if os == "none" || os == "unknown" {
+            None
+        } else {
+            Some("unix")
+        };
+        let pointer_width = match arch {
+            _ if abi == "x32" || raw_arch == "arm64_32" => "32",
+            "x86_64" | "aarch64" | "riscv64" | "powerpc64" | "mips64"
+            | "sparc64" | "s390x" | "loongarch64" | "wasm64" => "64",
+            "avr" | "msp430" => "16",
+            _ => "32",
+        };
+        let is_big_endian = matches!(
+            raw_arch,
+            "powerpc"
+                | "powerpc64"
+                | "mips"
+                | "mips64"
+                | "s390x"
+                | "sparc"
+                | "sparc64"
+                | "sparcv9"
+                | "aarch64_be"
+        ) || raw_arch.starts_with("armeb")
+            || raw_arch.starts_with("thumbeb");
+        Self {
+            arch,
+            vendor,
+            os,
+            env,
+            abi,
+            family,
+            pointer_width,
+            endian: if is_big_endian { "big" } else { "little" },
+        }
+    }
+}
+
+/// Splits the last part of a triple into the env and the abi, e.g. `gnu`
+/// and `eabihf` for `gnueabihf`, or nothing and `eabihf` for `eabihf`.
+fn split_env_abi(env_abi: &str) -> (&str, &str) {
+    for env in ["gnu", "musl", "uclibc", "msvc", "sgx", "newlib", "ohos"] {
+        if let Some(abi) = env_abi.strip_prefix(env) {
+            return (env, abi);
+        }
+    }
+    ("", env_abi)
+}
+
+/// Returns the `target_arch` of the architecture of a triple, which drops
+/// the variants, e.g. `arm` for `armv7` and `riscv64` for `riscv64gc`.
+fn normalize_arch(arch: &str) -> &str {
+    match arch {
+        "i386" | "i586" | "i686" => "x86",
+        "aarch64_be" | "arm64_32" => "aarch64",
+        "powerpc64le" => "powerpc64",
+        "mipsel" => "mips",
+        "mips64el" => "mips64",
+        "sparcv9" => "sparc64",
+        arch if arch.starts_with("arm") || arch.starts_with("thumb") => "arm",
+        arch if arch.starts_with("riscv64") => "riscv64",
+        arch if arch.starts_with("riscv32") => "riscv32",
+        arch => arch,
+    }
+}
+
+/// Returns the attributes of the item, or none for verbatim tokens.
+fn item_attrs(item: &Item) -> &[Attribute] {
+    match item {
+        Item::Const(item) => &item.attrs,
+        Item::Enum(item) => &item.attrs,
+        Item::ExternCrate(item) => &item.attrs,
+        Item::Fn(item) => &item.attrs,
+        Item::ForeignMod(item) => &item.attrs,
+        Item::Impl(item) => &item.attrs,
+        Item::Macro(item) => &item.attrs,
+        Item::Mod(item) => &item.attrs,
+        Item::Static(item) => &item.attrs,
+        Item::Struct(item) => &item.attrs,
+        Item::Trait(item) => &item.attrs,
+        Item::TraitAlias(item) => &item.attrs,
+        Item::Type(item) => &item.attrs,
+        Item::Union(item) => &item.attrs,
+        Item::Use(item) => &item.attrs,
+        _ => &[],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Evaluates the cfg predicate.
+    fn eval(cfg: &RuaCfg, pred: &str) -> Result<bool, ConversionError> {
+        cfg.eval(&syn::parse_str::<Meta>(pred).unwrap())
+    }
+
+    #[test]
+    fn eval_combines_predicates() {
+        let mut cfg = RuaCfg::new();
+        cfg.enable_flag("unix").enable_feature("serde");
+        assert!(eval(&cfg, "unix").unwrap());
+        assert!(!eval(&cfg, "windows").unwrap());
+        assert!(eval(&cfg, r#"feature = "serde""#).unwrap());
+        assert!(!eval(&cfg, r#"feature = "json""#).unwrap());
+        assert!(eval(&cfg, r#"all(unix, feature = "serde")"#).unwrap());
+        assert!(!eval(&cfg, r#"all(unix, feature = "json")"#).unwrap());
+        assert!(eval(&cfg, "all()").unwrap());
+        assert!(eval(&cfg, "any(windows, unix)").unwrap());
+        assert!(!eval(&cfg, "any()").unwrap());
+        assert!(eval(&cfg, "not(windows)").unwrap());
+        assert!(eval(&cfg, "any(windows, not(any(windows)))").unwrap());
+    }
+
+    #[test]
+    fn eval_rejects_invalid_predicates() {
+        let cfg = RuaCfg::new();
+        for (pred, message) in [
+            ("a::b", "cfg option must be an identifier"),
+            ("a::b = \"c\"", "cfg option must be an identifier"),
+            ("feature = 1", "cfg value must be a string literal"),
+            ("feature = serde", "cfg value must be a string literal"),
+            (
+                "not(unix, windows)",
+                "not() takes exactly one cfg predicate",
+            ),
+            ("not()", "not() takes exactly one cfg predicate"),
+            ("either(unix)", "unsupported cfg operator"),
+            ("all(unix windows)", "invalid cfg predicate"),
+        ] {
+            let err = eval(&cfg, pred).unwrap_err();
+            assert!(err.to_string().contains(message), "{}: {}", pred, err);
+        }
+    }
+
+    #[test]
+    fn is_enabled_requires_all_cfg_attributes() {
+        let item = syn::parse_str::<Item>(
+            "#[cfg(unix)] #[inline] #[cfg(feature = \"ffi\")] fn f() {}",
+        )
+        .unwrap();
+        let mut cfg = RuaCfg::new();
+        cfg.enable_flag("unix");
+        assert!(!cfg.is_item_enabled(&item).unwrap());
+        cfg.enable_feature("ffi");
+        assert!(cfg.is_item_enabled(&item).unwrap());
+        let item = syn::parse_str::<Item>("#[cfg] fn f() {}").unwrap();
+        assert!(cfg.is_item_enabled(&item).is_err());
+    }
+
+    #[test]
+    fn set_target_derives_the_options_of_the_triple() {
+        let cases = [
+            (
+                "x86_64-unknown-linux-gnu",
+                ["x86_64", "unknown", "linux", "gnu", "", "64", "little"],
+                Some("unix"),
+            ),
+            (
+                "armv7-unknown-linux-gnueabihf",
+                ["arm", "unknown", "linux", "gnu", "eabihf", "32", "little"],
+                Some("unix"),
+            ),
+            (
+                "thumbv7em-none-eabihf",
+                ["arm", "unknown", "none", "", "eabihf", "32", "little"],
+                None,
+            ),
+            (
+                "riscv64gc-unknown-linux-gnu",
+                ["riscv64", "unknown", "linux", "gnu", "", "64", "little"],
+                Some("unix"),
+            ),
+            (
+                "aarch64-linux-android",
+                ["aarch64", "unknown", "android", "", "", "64", "little"],
+                Some("unix"),
+            ),
+            (
+                "armv7-linux-androideabi",
+                ["arm", "unknown", "android", "", "eabi", "32", "little"],
+                Some("unix"),
+            ),
+            (
+                "aarch64-apple-ios-sim",
+                ["aarch64", "apple", "ios", "", "sim", "64", "little"],
+                Some("unix"),
+            ),
+            (
+                "x86_64-apple-darwin",
+                ["x86_64", "apple", "macos", "", "", "64", "little"],
+                Some("unix"),
+            ),
+            (
+                "i686-pc-windows-msvc",
+                ["x86", "pc", "windows", "msvc", "", "32", "little"],
+                Some("windows"),
+            ),
+            (
+                "wasm32-unknown-unknown",
+                ["wasm32", "unknown", "unknown", "", "", "32", "little"],
+                Some("wasm"),
+            ),
+            (
+                "wasm32-wasip1",
+                ["wasm32", "unknown", "wasi", "p1", "", "32", "little"],
+                Some("wasm"),
+            ),
+            (
+                "powerpc64-unknown-linux-gnu",
+                ["powerpc64", "unknown", "linux", "gnu", "", "64", "big"],
+                Some("unix"),
+            ),
+            (
+                "x86_64-unknown-linux-gnux32",
+                ["x86_64", "unknown", "linux", "gnu", "x32", "32", "little"],
+                Some("unix"),
+            ),
+        ];
+        let keys = [
+            "target_arch",
+            "target_vendor",
+            "target_os",
+            "target_env",
+            "target_abi",
+            "target_pointer_width",
+            "target_endian",
+        ];
+        for (triple, values, family) in cases {
+            let mut cfg = RuaCfg::new();
+            cfg.set_target(triple);
+            assert_eq!(cfg.target(), Some(triple));
+            for (key, value) in keys.iter().zip(values) {
+                let pred = format!("{} = {:?}", key, value);
+                assert!(eval(&cfg, &pred).unwrap(), "{}: {}", triple, pred);
+            }
+            for other in ["unix", "windows", "wasm"] {
+                let pred = format!("target_family = {:?}", other);
+                let holds = family == Some(other);
+                assert_eq!(eval(&cfg, &pred).unwrap(), holds, "{}", triple);
+            }
+            let is_unix = family == Some("unix");
+            assert_eq!(eval(&cfg, "unix").unwrap(), is_unix, "{}", triple);
+        }
+    }
+}
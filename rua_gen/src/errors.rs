@@ -92,15 +92,27 @@ impl Error for ParseError {
     }
 }
 
-/// An error that occurs during a conversion.
+/// An error that occurs during a conversion. The details are boxed to keep
+/// the `Result`s returned by the conversions small.
 #[derive(Debug, Clone, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
 pub struct ConversionError {
+    data: Box<ConversionErrorData>,
+}
+
+/// The details of a [ConversionError].
+#[derive(Debug, Clone, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+struct ConversionErrorData {
     /// The file that caused the error.
     path: Option<PathBuf>,
     /// The start of the error.
     start: Option<(usize, usize)>,
     /// The end of the error.
     end: Option<(usize, usize)>,
+    /// The byte range of the error in the file.
+    byte_range: Option<(usize, usize)>,
     /// The source type
     source_type: Option<String>,
     /// The target type.
@@ -108,34 +120,36 @@ pub struct ConversionError {
     /// The message of this error.
     message: Option<String>,
     /// The source of this error.
-    err_source: Option<Box<ConversionError>>,
+    #[cfg_attr(feature = "serde", serde(rename = "cause"))]
+    err_source: Option<ConversionError>,
 }
 
 impl std::fmt::Display for ConversionError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        if let Some(path) = &self.path {
+        let data = &self.data;
+        if let Some(path) = &data.path {
             write!(f, "{}", path.display())?;
         }
-        if let Some((line, column)) = &self.start {
+        if let Some((line, column)) = &data.start {
             write!(f, "(from {}:{}", line, column)?;
         } else {
             write!(f, "(")?;
         }
-        if let Some((line, column)) = &self.end {
+        if let Some((line, column)) = &data.end {
             write!(f, "to {}:{})", line, column)?;
         } else {
             write!(f, ")")?;
         }
-        if let Some(source_type) = &self.source_type {
+        if let Some(source_type) = &data.source_type {
             write!(f, " from {}", source_type)?;
         }
-        if let Some(target_type) = &self.target_type {
+        if let Some(target_type) = &data.target_type {
             write!(f, " to {}", target_type)?;
         }
-        if let Some(message) = &self.message {
+        if let Some(message) = &data.message {
             write!(f, ": {}", message)?;
         }
-        if let Some(err_source) = &self.err_source {
+        if let Some(err_source) = &data.err_source {
             write!(f, "\n- Caused by: {}", err_source)?;
         }
         Ok(())
@@ -144,10 +158,10 @@ impl std::fmt::Display for ConversionError {
 
 impl Error for ConversionError {
     fn source(&self) -> Option<&(dyn Error + 'static)> {
-        match self.err_source {
-            Some(ref e) => Some(&**e),
-            None => None,
-        }
+        self.data
+            .err_source
+            .as_ref()
+            .map(|e| e as &(dyn Error + 'static))
     }
 }
 
@@ -157,19 +171,44 @@ pub struct ConversionErrorBuilder {
     path: Option<PathBuf>,
     start: Option<(usize, usize)>,
     end: Option<(usize, usize)>,
+    byte_range: Option<(usize, usize)>,
     source_type: Option<String>,
     target_type: Option<String>,
     message: Option<String>,
-    err_source: Option<Box<ConversionError>>,
+    err_source: Option<ConversionError>,
 }
 
 impl ConversionError {
+    /// Returns the byte range of the error in the file, if it is known.
+    pub fn byte_range(&self) -> Option<(usize, usize)> {
+        self.data.byte_range
+    }
+
+    /// Computes the byte ranges of the error and of its sources from their
+    /// start and end, given the contents of the file that caused the error.
+    /// The ranges stay unknown where a location is missing or out of bounds.
+    pub fn locate_in(&mut self, contents: &str) {
+        let data = &mut self.data;
+        if let (Some(start), Some(end)) = (data.start, data.end) {
+            if let (Some(start), Some(end)) = (
+                location_to_offset(contents, start),
+                location_to_offset(contents, end),
+            ) {
+                data.byte_range = Some((start, end));
+            }
+        }
+        if let Some(err_source) = &mut data.err_source {
+            err_source.locate_in(contents);
+        }
+    }
+
     /// Creates a new conversion error builder.
     pub fn builder() -> ConversionErrorBuilder {
         ConversionErrorBuilder {
             path: None,
             start: None,
             end: None,
+            byte_range: None,
             source_type: None,
             target_type: None,
             err_source: None,
@@ -183,10 +222,11 @@ impl ConversionError {
             path: None,
             start: None,
             end: None,
+            byte_range: None,
             source_type: None,
             target_type: None,
             message: None,
-            err_source: Some(Box::new(self.clone())),
+            err_source: Some(self.clone()),
         }
     }
 }
@@ -219,6 +259,12 @@ impl ConversionErrorBuilder {
         self
     }
 
+    /// Sets the byte range of the error.
+    pub fn byte_range(&mut self, range: (usize, usize)) -> &mut Self {
+        self.byte_range = Some(range);
+        self
+    }
+
     /// Sets the source type.
     pub fn source_type(&mut self, source_type: impl AsRef<str>) -> &mut Self {
         self.source_type = Some(source_type.as_ref().to_owned());
@@ -240,15 +286,160 @@ impl ConversionErrorBuilder {
     /// Builds the conversion error.
     pub fn build(&mut self) -> ConversionError {
         let error = ConversionError {
-            path: self.path.take(),
-            start: self.start.take(),
-            end: self.end.take(),
-            source_type: self.source_type.take(),
-            target_type: self.target_type.take(),
-            err_source: self.err_source.take(),
-            message: self.message.take(),
+            data: Box::new(ConversionErrorData {
+                path: self.path.take(),
+                start: self.start.take(),
+                end: self.end.take(),
+                byte_range: self.byte_range.take(),
+                source_type: self.source_type.take(),
+                target_type: self.target_type.take(),
+                err_source: self.err_source.take(),
+                message: self.message.take(),
+            }),
         };
         log::debug!("ConversionError: {}", error);
         error
     }
 }
+
+/// Converts a location as reported by [Span], i.e. a 1-indexed line and a
+/// 0-indexed column counted in chars, to a byte offset into the contents.
+fn location_to_offset(
+    contents: &str,
+    (line, column): (usize, usize),
+) -> Option<usize> {
+    let line_start = match line {
+        0 => return None,
+        1 => 0,
+        _ => contents.match_indices('\n').nth(line - 2)?.0 + 1,
+    };
+    let line_contents = contents[line_start..].split('\n').next()?;
+    if column == line_contents.chars().count() {
+        return Some(line_start + line_contents.len());
+    }
+    line_contents
+        .char_indices()
+        .nth(column)
+        .map(|(offset, _)| line_start + offset)
+}
+
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use serde::{
+        ser::{SerializeStruct, SerializeStructVariant},
+        Serialize, Serializer,
+    };
+
+    use super::*;
+
+    impl Serialize for RuaError {
+        fn serialize<S: Serializer>(
+            &self,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            match self {
+                RuaError::FsError(e) => serializer
+                    .serialize_newtype_variant("RuaError", 0, "FsError", e),
+                RuaError::ParseError(e) => serializer
+                    .serialize_newtype_variant("RuaError", 1, "ParseError", e),
+                RuaError::ConversionError(e) => serializer
+                    .serialize_newtype_variant(
+                        "RuaError",
+                        2,
+                        "ConversionError",
+                        e,
+                    ),
+            }
+        }
+    }
+
+    /// The boxed errors are serialized as their messages.
+    impl Serialize for RuaFsError {
+        fn serialize<S: Serializer>(
+            &self,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            match self {
+                RuaFsError::ReadFileErr { path, err } => {
+                    let mut state = serializer.serialize_struct_variant(
+                        "RuaFsError",
+                        0,
+                        "ReadFileErr",
+                        2,
+                    )?;
+                    state.serialize_field("path", path)?;
+                    state.serialize_field("err", &err.to_string())?;
+                    state.end()
+                }
+                RuaFsError::FileNotFoundErr(path) => serializer
+                    .serialize_newtype_variant(
+                        "RuaFsError",
+                        1,
+                        "FileNotFoundErr",
+                        path,
+                    ),
+            }
+        }
+    }
+
+    /// The boxed error is serialized as its message.
+    impl Serialize for ParseError {
+        fn serialize<S: Serializer>(
+            &self,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            let mut state = serializer.serialize_struct("ParseError", 2)?;
+            state.serialize_field("path", &self.path)?;
+            state.serialize_field("err", &self.err.to_string())?;
+            state.end()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::RuaStruct;
+
+    #[test]
+    fn byte_range_points_at_the_snippet() {
+        let contents = "// é\nstruct Café {\n    x: <T as A>::B,\n}\n";
+        let file = syn::parse_file(contents).unwrap();
+        let item = match &file.items[0] {
+            syn::Item::Struct(item) => item,
+            _ => panic!("expected a struct"),
+        };
+        let mut err = RuaStruct::try_from(item).unwrap_err();
+        err.locate_in(contents);
+        let (start, end) = err.byte_range().unwrap();
+        assert!(contents[start..end].starts_with("struct Café"));
+        let mut innermost: &dyn Error = &err;
+        while let Some(source) = innermost.source() {
+            innermost = source;
+        }
+        let innermost = innermost.downcast_ref::<ConversionError>().unwrap();
+        let (start, end) = innermost.byte_range().unwrap();
+        assert_eq!(&contents[start..end], "<T as A>::B");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serializes_the_chain_of_causes() {
+        let item =
+            syn::parse_str::<syn::ItemStruct>("struct Foo { x: <T as A>::B }")
+                .unwrap();
+        let err = RuaStruct::try_from(&item).unwrap_err();
+        let json = serde_json::to_value(&err).unwrap();
+        assert_eq!(json["source_type"], "syn::ItemStruct");
+        assert_eq!(json["target_type"], "RuaStruct");
+        assert!(
+            json["start"].is_array() && json["end"].is_array(),
+            "{}",
+            json
+        );
+        let cause = &json["cause"];
+        assert!(cause.is_object(), "{}", json);
+        assert!(cause["source_type"].is_string(), "{}", json);
+        assert!(cause["start"].is_array(), "{}", json);
+    }
+}
@@ -1,8 +1,16 @@
 //! The generator for `rua`.
 #![warn(missing_docs, rust_2018_idioms)]
+pub use cfg::*;
+pub mod cfg;
 pub use errors::*;
 pub mod errors;
 pub use logic::*;
 pub mod logic;
 pub use models::*;
 pub mod models;
+pub use recursion::*;
+pub mod recursion;
+pub use render::*;
+pub mod render;
+#[cfg(any(test, feature = "test-support"))]
+pub mod test_support;
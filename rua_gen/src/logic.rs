@@ -2,10 +2,68 @@
 use std::path::{Path, PathBuf};
 
 use crate::{
-    errors::RuaFsError,
-    models::{RuaEnum, RuaFn, RuaMod, RuaStruct},
+    cfg::RuaCfg,
+    errors::{ConversionError, ParseError, RuaError, RuaFsError},
+    models::{
+        convert_item, RuaEnum, RuaFn, RuaImpl, RuaItem, RuaMod, RuaNamed,
+        RuaSigFn, RuaStruct, RuaTrait, RuaType,
+    },
+    recursion::check_recursive_types,
 };
 
+/// Reads the `.rs` file at the path and converts its top-level items, in
+/// order, with [convert_items].
+pub fn parse_file(
+    path: impl AsRef<Path>,
+    cfg: Option<&RuaCfg>,
+) -> Result<Vec<RuaItem>, RuaError> {
+    let path = path.as_ref();
+    let contents = std::fs::read_to_string(path).map_err(|e| {
+        RuaError::FsError(RuaFsError::ReadFileErr {
+            path: path.to_path_buf(),
+            err: Box::new(e),
+        })
+    })?;
+    let file = syn::parse_file(&contents).map_err(|e| {
+        RuaError::ParseError(ParseError {
+            path: path.to_path_buf(),
+            err: Box::new(e),
+        })
+    })?;
+    convert_items(&file.items, cfg).map_err(|err| {
+        let mut err = err
+            .builder_for_next()
+            .path(path.to_path_buf())
+            .source_type("syn::File")
+            .target_type("RuaItem")
+            .build();
+        err.locate_in(&contents);
+        RuaError::ConversionError(err)
+    })
+}
+
+/// Converts the items, in order, with [convert_item]. Items without a model,
+/// e.g. `use` declarations, are skipped, and so are nested modules, which are
+/// not followed. With a `cfg`, the items disabled by their `#[cfg(...)]`
+/// attributes are skipped too, otherwise all of them are converted.
+pub fn convert_items(
+    items: &[syn::Item],
+    cfg: Option<&RuaCfg>,
+) -> Result<Vec<RuaItem>, ConversionError> {
+    let mut res = Vec::new();
+    for item in items.iter() {
+        if let Some(cfg) = cfg {
+            if !cfg.is_item_enabled(item)? {
+                continue;
+            }
+        }
+        if let Some(item) = convert_item(item)? {
+            res.push(item);
+        }
+    }
+    Ok(res)
+}
+
 /// Implement this trait to build your own code generator.
 pub trait Rua {
     /// Returns the path to the entry point of the module, i.e. the path to the
@@ -41,6 +99,134 @@ pub trait Rua {
         })
     }
 
+    /// Returns the banner to put at the top of each generated file. The
+    /// default uses `//` comments, override it for targets with a different
+    /// comment syntax.
+    fn file_header(&self) -> String {
+        format!(
+            "// GENERATED CODE - DO NOT MODIFY BY HAND\n\
+             // Generated by rua v{}.\n",
+            env!("CARGO_PKG_VERSION")
+        )
+    }
+
+    /// Checks if the target can represent the type. The whole type is
+    /// passed, so implementations should look into the element types of
+    /// composite types themselves if needed. By default, all types are
+    /// supported.
+    fn supports(&self, _ty: &RuaType) -> bool {
+        true
+    }
+
+    /// Checks that all the types used by the item are supported, see
+    /// [Rua::supports]. Call it before writing anything, so that an
+    /// unsupported type is reported with the item it appears in, e.g.
+    /// "unsupported type u128 of field `x` in struct `Y`".
+    fn check_supported(&self, item: &RuaItem) -> Result<(), ConversionError> {
+        let mut tys = Vec::new();
+        match item {
+            RuaItem::Struct(s) => collect_struct_tys(s, "", &mut tys),
+            RuaItem::Enum(e) => {
+                for variant in e.variants.iter() {
+                    let prefix =
+                        format!("variant `{}` ", variant.name().render());
+                    collect_struct_tys(variant, &prefix, &mut tys);
+                }
+            }
+            RuaItem::Fn(f) => collect_fn_tys(f, "", &mut tys),
+            RuaItem::Trait(t) => {
+                for method in t.methods.iter() {
+                    let prefix = format!("method `{}` ", method.name.render());
+                    collect_fn_tys(method, &prefix, &mut tys);
+                }
+            }
+            RuaItem::Impl(i) => {
+                for method in i.methods.iter() {
+                    let prefix = format!("method `{}` ", method.name.render());
+                    collect_fn_tys(method, &prefix, &mut tys);
+                }
+            }
+        }
+        match tys.into_iter().find(|(ty, _)| !self.supports(ty)) {
+            Some((ty, what)) => Err(ConversionError::builder()
+                .source_type("RuaType")
+                .message(format!(
+                    "unsupported type {} of {} in {} `{}`",
+                    ty,
+                    what,
+                    item_kind(item),
+                    item.name().render()
+                ))
+                .build()),
+            None => Ok(()),
+        }
+    }
+
+    /// Checks the items of a file, e.g. in CI, without writing anything:
+    /// converts them like [convert_items], checks that their types are
+    /// supported with [Rua::check_supported], that no two of them have names
+    /// that collide once rendered in a single case, see
+    /// [crate::models::RuaName::same_identity], and that the structs and
+    /// enums do not contain themselves with [check_recursive_types]. Unlike
+    /// those, it does not stop at the first error, so all of them are
+    /// returned.
+    fn validate(
+        &self,
+        items: &[syn::Item],
+        cfg: Option<&RuaCfg>,
+    ) -> Vec<RuaError> {
+        let mut errors = Vec::new();
+        let mut converted = Vec::new();
+        for item in items.iter() {
+            let item =
+                match cfg.map_or(Ok(true), |cfg| cfg.is_item_enabled(item)) {
+                    Ok(true) => convert_item(item),
+                    Ok(false) => Ok(None),
+                    Err(err) => Err(err),
+                };
+            match item {
+                Ok(Some(item)) => converted.push(item),
+                Ok(None) => {}
+                Err(err) => errors.push(RuaError::ConversionError(err)),
+            }
+        }
+        for item in converted.iter() {
+            if let Err(err) = self.check_supported(item) {
+                errors.push(RuaError::ConversionError(err));
+            }
+        }
+        // impl blocks share the name of their type
+        let named = converted
+            .iter()
+            .filter(|item| !matches!(item, RuaItem::Impl(_)))
+            .collect::<Vec<_>>();
+        for (i, item) in named.iter().enumerate() {
+            for other in named[i + 1..].iter() {
+                if item.name().same_identity(other.name()) {
+                    errors.push(RuaError::ConversionError(
+                        ConversionError::builder()
+                            .source_type("RuaItem")
+                            .message(format!(
+                                "{} `{}` and {} `{}` collide once rendered \
+                                 in a single case",
+                                item_kind(item),
+                                item.name().render(),
+                                item_kind(other),
+                                other.name().render()
+                            ))
+                            .build(),
+                    ));
+                }
+            }
+        }
+        let root = Vec::new();
+        let items = converted.iter().map(|item| (root.as_slice(), item));
+        if let Err(err) = check_recursive_types(items) {
+            errors.push(RuaError::ConversionError(err));
+        }
+        errors
+    }
+
     /// Generates and writes the function.
     fn write_fn(&mut self, m: &RuaMod, f: &RuaFn);
 
@@ -49,4 +235,185 @@ pub trait Rua {
 
     /// Generates and writes the enum.
     fn write_enum(&mut self, m: &RuaMod, e: &RuaEnum);
+
+    /// Generates and writes the trait. By default, traits are skipped.
+    fn write_trait(&mut self, _m: &RuaMod, _t: &RuaTrait) {}
+
+    /// Generates and writes the methods of the impl block. By default, impl
+    /// blocks are skipped.
+    fn write_impl(&mut self, _m: &RuaMod, _i: &RuaImpl) {}
+}
+
+/// Returns the kind of the item as written in Rust, e.g. `struct`.
+fn item_kind(item: &RuaItem) -> &'static str {
+    match item {
+        RuaItem::Struct(_) => "struct",
+        RuaItem::Enum(_) => "enum",
+        RuaItem::Fn(_) => "fn",
+        RuaItem::Trait(_) => "trait",
+        RuaItem::Impl(_) => "impl",
+    }
+}
+
+/// Collects the types of the fields of the struct along with their
+/// descriptions, e.g. "field `x`".
+fn collect_struct_tys<'a>(
+    s: &'a RuaStruct,
+    prefix: &str,
+    tys: &mut Vec<(&'a RuaType, String)>,
+) {
+    match s {
+        RuaStruct::Named(named) => {
+            for field in named.fields.iter() {
+                let what = format!("{}field `{}`", prefix, field.name.render());
+                tys.push((&field.ty, what));
+            }
+        }
+        RuaStruct::Tuple(tuple) => {
+            for (i, ty) in tuple.tys.iter().enumerate() {
+                tys.push((ty, format!("{}field `{}`", prefix, i)));
+            }
+        }
+        RuaStruct::Unit(_) => {}
+    }
+}
+
+/// Collects the types of the parameters and the return type of the function
+/// along with their descriptions, e.g. "parameter `x`".
+fn collect_fn_tys<'a>(
+    f: &'a RuaSigFn,
+    prefix: &str,
+    tys: &mut Vec<(&'a RuaType, String)>,
+) {
+    for param in f.params.iter() {
+        let what = format!("{}parameter `{}`", prefix, param.name.render());
+        tys.push((&param.ty, what));
+    }
+    tys.push((&f.ret, format!("{}return type", prefix)));
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::*;
+    use crate::test_support::convert_file;
+
+    const SRC: &str = r#"
+        pub struct Always;
+        #[cfg(feature = "extra")]
+        pub struct Extra;
+        #[cfg(not(target_os = "ios"))]
+        pub fn desktop_only() {}
+    "#;
+
+    /// Converts the items of [SRC] and returns their names.
+    fn names(cfg: Option<&RuaCfg>) -> Vec<String> {
+        convert_file(SRC, cfg)
+            .iter()
+            .map(|item| item.name().render().to_owned())
+            .collect()
+    }
+
+    /// A generator that does not support 128-bit integers.
+    struct NoWideInts;
+
+    impl Rua for NoWideInts {
+        fn entry_path(&self) -> PathBuf {
+            PathBuf::new()
+        }
+
+        fn supports(&self, ty: &RuaType) -> bool {
+            !ty.contains(|ty| matches!(ty, RuaType::I128 | RuaType::U128))
+        }
+
+        fn write_fn(&mut self, _m: &RuaMod, _f: &RuaFn) {}
+
+        fn write_struct(&mut self, _m: &RuaMod, _s: &RuaStruct) {}
+
+        fn write_enum(&mut self, _m: &RuaMod, _e: &RuaEnum) {}
+    }
+
+    #[test]
+    fn validate_reports_all_errors() {
+        let file = syn::parse_file(
+            "pub struct Id(u128);
+             pub struct Node { next: Node }
+             pub fn item(x: <u8 as Trait>::Item) {}
+             pub struct Fine { x: u32 }",
+        )
+        .unwrap();
+        let errors = NoWideInts
+            .validate(&file.items, None)
+            .iter()
+            .map(|err| err.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(errors.len(), 3, "{:?}", errors);
+        assert!(errors[0].contains("unsupported qualified path"));
+        assert!(errors[1].contains("unsupported type u128"));
+        assert!(errors[2].contains("Node -> Node"));
+    }
+
+    #[test]
+    fn validate_reports_colliding_names() {
+        let file = syn::parse_file(
+            "pub struct FooBar;
+             impl FooBar { pub fn new() -> u8 { 0 } }
+             pub fn foo_bar() {}
+             pub fn foo_baz() {}",
+        )
+        .unwrap();
+        let errors = NoWideInts.validate(&file.items, None);
+        assert_eq!(errors.len(), 1, "{:?}", errors);
+        assert!(errors[0]
+            .to_string()
+            .contains("struct `FooBar` and fn `foo_bar` collide"));
+    }
+
+    #[test]
+    fn parse_file_converts_the_items_of_a_file() {
+        let mut file =
+            tempfile::Builder::new().suffix(".rs").tempfile().unwrap();
+        write!(
+            file,
+            "use std::fmt;
+             pub struct Point {{ x: f32, y: f32 }}
+             pub enum Shape {{ Circle(f32), Square(f32) }}
+             pub fn area(shape: Shape) -> f32 {{ 0.0 }}"
+        )
+        .unwrap();
+        let items = parse_file(file.path(), None).unwrap();
+        assert!(matches!(
+            items.as_slice(),
+            [RuaItem::Struct(_), RuaItem::Enum(_), RuaItem::Fn(_)]
+        ));
+        let names = items
+            .iter()
+            .map(|item| item.name().render())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["Point", "Shape", "area"]);
+    }
+
+    #[test]
+    fn parse_file_reports_the_path_of_errors() {
+        let mut file =
+            tempfile::Builder::new().suffix(".rs").tempfile().unwrap();
+        write!(file, "pub fn f(x: <u8 as Trait>::Item) {{}}").unwrap();
+        let err = parse_file(file.path(), None).unwrap_err();
+        let name = file.path().file_name().unwrap().to_str().unwrap();
+        assert!(err.to_string().contains(name), "{}", err);
+        assert!(matches!(err, RuaError::ConversionError(_)));
+        let missing = file.path().with_extension("missing");
+        let err = parse_file(&missing, None).unwrap_err();
+        assert!(matches!(err, RuaError::FsError(_)));
+    }
+
+    #[test]
+    fn convert_items_skips_disabled_items() {
+        assert_eq!(names(None), ["Always", "Extra", "desktop_only"]);
+        let mut cfg = RuaCfg::new();
+        assert_eq!(names(Some(&cfg)), ["Always", "desktop_only"]);
+        cfg.enable_feature("extra").set_target("aarch64-apple-ios");
+        assert_eq!(names(Some(&cfg)), ["Always", "Extra"]);
+    }
 }
@@ -1,14 +1,233 @@
 //! The models used by `rua_gen`.
 use std::path::PathBuf;
 
-use crate::errors::ConversionError;
+use crate::{cfg::RuaCfg, errors::ConversionError};
 use rua_macros::rua_model_derive;
 
+use rua_attr::*;
+
+/// Helpers for the `#[rua(...)]` attributes.
+mod rua_attr {
+    use proc_macro2::Ident;
+    use syn::{
+        parse::ParseStream, punctuated::Punctuated, spanned::Spanned,
+        Attribute, Expr, ExprLit, Lit, LitStr, Meta, MetaNameValue, Path,
+        Token,
+    };
+
+    use super::*;
+
+    /// Returns the arguments of all the `#[rua(...)]` attributes, e.g.
+    /// `default = "0"` for `#[rua(default = "0")]`.
+    pub(crate) fn rua_attr_args(
+        attrs: &[Attribute],
+    ) -> Result<Vec<Meta>, ConversionError> {
+        let mut args = Vec::new();
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("rua")) {
+            match &attr.meta {
+                Meta::Path(_) => {}
+                Meta::List(list) => {
+                    let parsed = list.parse_args_with(parse_rua_args).map_err(
+                        |err| {
+                            ConversionError::builder()
+                                .span(&err.span())
+                                .source_type("syn::Attribute")
+                                .target_type("Meta")
+                                .message(format!(
+                                    "invalid rua attribute: {}",
+                                    err
+                                ))
+                                .build()
+                        },
+                    )?;
+                    args.extend(parsed);
+                }
+                Meta::NameValue(_) => {
+                    return Err(ConversionError::builder()
+                        .span(&attr.span())
+                        .source_type("syn::Attribute")
+                        .target_type("Meta")
+                        .message("expected #[rua] or #[rua(...)]")
+                        .build())
+                }
+            }
+        }
+        Ok(args)
+    }
+
+    /// Checks if the attributes contain `#[non_exhaustive]`.
+    pub(crate) fn is_non_exhaustive(attrs: &[Attribute]) -> bool {
+        attrs
+            .iter()
+            .any(|attr| attr.path().is_ident("non_exhaustive"))
+    }
+
+    /// Parses the comma-separated arguments of `#[rua(...)]`.
+    fn parse_rua_args(
+        input: ParseStream<'_>,
+    ) -> syn::Result<Punctuated<Meta, Token![,]>> {
+        Punctuated::parse_terminated_with(input, parse_rua_arg)
+    }
+
+    /// Parses an argument of `#[rua(...)]`. Unlike [Meta], the key may be the
+    /// `as` keyword, i.e. `as = "T"`.
+    fn parse_rua_arg(input: ParseStream<'_>) -> syn::Result<Meta> {
+        if input.peek(Token![as]) {
+            let as_token: Token![as] = input.parse()?;
+            return Ok(Meta::NameValue(MetaNameValue {
+                path: Path::from(Ident::new("as", as_token.span)),
+                eq_token: input.parse()?,
+                value: input.parse()?,
+            }));
+        }
+        input.parse()
+    }
+
+    /// Returns the value of the `key = "value"` argument, if present.
+    pub(crate) fn rua_attr_str(
+        args: &[Meta],
+        key: &str,
+    ) -> Result<Option<String>, ConversionError> {
+        Ok(rua_attr_lit(args, key)?.map(|lit| lit.value()))
+    }
+
+    /// Returns the string literal of the `key = "value"` argument, if
+    /// present, e.g. to parse it with the span of the literal.
+    pub(crate) fn rua_attr_lit(
+        args: &[Meta],
+        key: &str,
+    ) -> Result<Option<LitStr>, ConversionError> {
+        for arg in args.iter() {
+            let name_value = match arg {
+                Meta::NameValue(name_value)
+                    if name_value.path.is_ident(key) =>
+                {
+                    name_value
+                }
+                _ => continue,
+            };
+            return meta_lit(name_value, key).map(Some);
+        }
+        Ok(None)
+    }
+
+    /// Returns the doc comments in the attributes, one line per `///` line
+    /// without the leading space, or `None` if there are none.
+    pub(crate) fn doc_comments(attrs: &[Attribute]) -> Option<String> {
+        let lines = attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("doc"))
+            .filter_map(|attr| match &attr.meta {
+                Meta::NameValue(MetaNameValue {
+                    value:
+                        Expr::Lit(ExprLit {
+                            lit: Lit::Str(s), ..
+                        }),
+                    ..
+                }) => Some(s.value()),
+                _ => None,
+            })
+            .map(|line| match line.strip_prefix(' ') {
+                Some(line) => line.to_owned(),
+                None => line,
+            })
+            .collect::<Vec<_>>();
+        if lines.is_empty() {
+            None
+        } else {
+            Some(lines.join("\n"))
+        }
+    }
+
+    /// Checks if the bare `key` argument is present, e.g. `transparent` in
+    /// `#[rua(transparent)]`.
+    pub(crate) fn rua_attr_flag(args: &[Meta], key: &str) -> bool {
+        args.iter()
+            .any(|arg| matches!(arg, Meta::Path(path) if path.is_ident(key)))
+    }
+
+    /// Returns the `name = "value"` pairs in the `key(...)` argument, e.g.
+    /// `T = "u32"` for `monomorphize(T = "u32")`, with the values as string
+    /// literals.
+    pub(crate) fn rua_attr_pairs(
+        args: &[Meta],
+        key: &str,
+    ) -> Result<Vec<(String, LitStr)>, ConversionError> {
+        let mut pairs = Vec::new();
+        for arg in args.iter() {
+            let list = match arg {
+                Meta::List(list) if list.path.is_ident(key) => list,
+                _ => continue,
+            };
+            let nested =
+                list.parse_args_with(parse_rua_args).map_err(|err| {
+                    ConversionError::builder()
+                        .span(&err.span())
+                        .source_type("syn::Meta")
+                        .target_type("Meta")
+                        .message(format!("invalid {} arguments: {}", key, err))
+                        .build()
+                })?;
+            for meta in nested.iter() {
+                let name_value = match meta {
+                    Meta::NameValue(name_value) => name_value,
+                    _ => {
+                        return Err(ConversionError::builder()
+                            .span(&meta.span())
+                            .source_type("syn::Meta")
+                            .target_type("String")
+                            .message(format!(
+                                "expected name = \"value\" in {}(...)",
+                                key
+                            ))
+                            .build())
+                    }
+                };
+                let name = match name_value.path.get_ident() {
+                    Some(ident) => ident.to_string(),
+                    None => {
+                        return Err(ConversionError::builder()
+                            .span(&name_value.path.span())
+                            .source_type("syn::Meta")
+                            .target_type("String")
+                            .message("expected an identifier")
+                            .build())
+                    }
+                };
+                let value = meta_lit(name_value, &name)?;
+                pairs.push((name, value));
+            }
+        }
+        Ok(pairs)
+    }
+
+    /// Returns the string literal of the `key = "value"` argument.
+    fn meta_lit(
+        name_value: &MetaNameValue,
+        key: &str,
+    ) -> Result<LitStr, ConversionError> {
+        match &name_value.value {
+            Expr::Lit(ExprLit {
+                lit: Lit::Str(s), ..
+            }) => Ok(s.clone()),
+            _ => Err(ConversionError::builder()
+                .span(&name_value.span())
+                .source_type("syn::Meta")
+                .target_type("String")
+                .message(format!("{} must be a string literal", key))
+                .build()),
+        }
+    }
+}
+
 pub use rua_name::*;
 
 /// Types related to names.
 mod rua_name {
-    use std::fmt::Display;
+    use std::{
+        collections::{HashMap, HashSet},
+        fmt::Display,
+    };
 
     use super::*;
 
@@ -21,13 +240,62 @@ mod rua_name {
         fn to_pascal_case(&self) -> String;
     }
 
+    /// Splits the leading underscores off a name. They mark unused or private
+    /// names rather than being part of the case, so they are kept as is.
+    fn split_leading_underscores(s: &str) -> (&str, &str) {
+        let rest = s.trim_start_matches('_');
+        s.split_at(s.len() - rest.len())
+    }
+
+    // names are almost always ASCII, so the Unicode tables are only looked up
+    // for the other characters
+
+    fn is_lower(c: char) -> bool {
+        if c.is_ascii() {
+            c.is_ascii_lowercase()
+        } else {
+            c.is_lowercase()
+        }
+    }
+
+    fn is_upper(c: char) -> bool {
+        if c.is_ascii() {
+            c.is_ascii_uppercase()
+        } else {
+            c.is_uppercase()
+        }
+    }
+
+    fn push_lower(res: &mut String, c: char) {
+        if c.is_ascii() {
+            res.push(c.to_ascii_lowercase());
+        } else {
+            res.extend(c.to_lowercase());
+        }
+    }
+
+    fn push_upper(res: &mut String, c: char) {
+        if c.is_ascii() {
+            res.push(c.to_ascii_uppercase());
+        } else {
+            res.extend(c.to_uppercase());
+        }
+    }
+
     impl<T: AsRef<str>> RuaCased for T {
         fn is_snake_case(&self) -> bool {
             let s = self.as_ref();
             if s.is_empty() {
                 return false;
             }
-            s.chars().all(|c| c.is_ascii_lowercase() || c == '_')
+            // letters without case, e.g. in CJK scripts, are allowed too
+            s.chars().all(|c| {
+                if c.is_ascii() {
+                    c == '_' || c.is_ascii_lowercase()
+                } else {
+                    c.is_alphabetic() && !c.is_uppercase()
+                }
+            })
         }
 
         fn is_camel_case(&self) -> bool {
@@ -39,7 +307,7 @@ mod rua_name {
                 return false;
             }
             match s.chars().next() {
-                Some(val) => val.is_ascii_lowercase(),
+                Some(val) => is_lower(val),
                 None => false,
             }
         }
@@ -53,46 +321,47 @@ mod rua_name {
                 return false;
             }
             match s.chars().next() {
-                Some(val) => val.is_ascii_uppercase(),
+                Some(val) => is_upper(val),
                 None => false,
             }
         }
 
         fn to_snake_case(&self) -> String {
-            let s = self.as_ref();
-            if s.is_empty() {
-                return String::new();
-            }
-            let mut chars = s.chars();
-            let first = chars.next();
-            // just to be safe
-            if first.is_none() {
-                return String::new();
-            }
-            let first = first.unwrap();
-            let rest = chars
-                .map(|c| {
-                    if c.is_ascii_uppercase() {
-                        format!("_{}", c.to_ascii_lowercase())
-                    } else {
-                        c.to_string()
+            let (prefix, s) = split_leading_underscores(self.as_ref());
+            let chars = s.chars().collect::<Vec<_>>();
+            let mut res = prefix.to_string();
+            for (i, &c) in chars.iter().enumerate() {
+                if !is_upper(c) {
+                    res.push(c);
+                    continue;
+                }
+                // a word starts after a lowercase letter or a digit, or at
+                // the last capital of an acronym, e.g. the `S` of
+                // `HTTPServer`
+                if i > 0 {
+                    let prev = chars[i - 1];
+                    let next_is_lower =
+                        chars.get(i + 1).is_some_and(|&next| is_lower(next));
+                    if is_lower(prev)
+                        || prev.is_numeric()
+                        || (is_upper(prev) && next_is_lower)
+                    {
+                        res.push('_');
                     }
-                })
-                .collect::<String>();
-            format!("{}{}", first.to_ascii_lowercase(), rest)
+                }
+                push_lower(&mut res, c);
+            }
+            res
         }
 
         fn to_camel_case(&self) -> String {
-            let s = self.as_ref();
-            if s.is_empty() {
-                return String::new();
-            }
+            let (prefix, s) = split_leading_underscores(self.as_ref());
             let chars = s.chars();
-            let mut res = String::new();
+            let mut res = prefix.to_string();
             let mut prev_is_dash = false;
             for (i, c) in chars.enumerate() {
                 if i == 0 {
-                    res.push(c.to_ascii_lowercase());
+                    push_lower(&mut res, c);
                     continue;
                 }
                 if c == '_' {
@@ -100,7 +369,7 @@ mod rua_name {
                     continue;
                 }
                 if prev_is_dash {
-                    res.push(c.to_ascii_uppercase());
+                    push_upper(&mut res, c);
                     prev_is_dash = false;
                     continue;
                 }
@@ -111,12 +380,14 @@ mod rua_name {
 
         fn to_pascal_case(&self) -> String {
             let camel = self.to_camel_case();
-            let first = camel.chars().next();
-            if first.is_none() {
-                return String::new();
+            let (prefix, s) = split_leading_underscores(&camel);
+            let mut chars = s.chars();
+            let mut res = prefix.to_string();
+            if let Some(first) = chars.next() {
+                push_upper(&mut res, first);
+                res.push_str(chars.as_str());
             }
-            let first = first.unwrap();
-            format!("{}{}", first.to_ascii_uppercase(), &camel[1..])
+            res
         }
     }
 
@@ -124,6 +395,67 @@ mod rua_name {
     pub trait RuaNamed {
         /// Returns the name of the item.
         fn name(&self) -> &RuaName;
+
+        /// Returns the name of the item as it should appear in generated
+        /// code, see [RuaNaming::apply].
+        fn rendered_name(&self, case: &RuaCase, naming: &RuaNaming) -> String {
+            naming.apply(self.name(), case)
+        }
+    }
+
+    /// The naming policy of a generator, i.e. the names that are renamed and
+    /// the reserved words of the target language.
+    #[derive(Debug, Clone, Default, PartialEq, Eq)]
+    pub struct RuaNaming {
+        /// The names to rename, keyed by the original identifier.
+        renames: HashMap<String, String>,
+        /// The reserved words of the target language.
+        reserved: HashSet<String>,
+    }
+
+    impl RuaNaming {
+        /// Creates a naming policy without renames and reserved words.
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Renames the identifier `from` to `to`. The new name is used as is,
+        /// without case conversion or sanitization.
+        pub fn rename(
+            &mut self,
+            from: impl AsRef<str>,
+            to: impl AsRef<str>,
+        ) -> &mut Self {
+            self.renames
+                .insert(from.as_ref().to_owned(), to.as_ref().to_owned());
+            self
+        }
+
+        /// Reserves the words, e.g. the keywords of the target language.
+        pub fn reserve<I, S>(&mut self, words: I) -> &mut Self
+        where
+            I: IntoIterator<Item = S>,
+            S: AsRef<str>,
+        {
+            self.reserved
+                .extend(words.into_iter().map(|word| word.as_ref().to_owned()));
+            self
+        }
+
+        /// Returns the name in the case, or its rename if it has one. Names
+        /// that end up being a reserved word get a trailing `_`, e.g. `type`
+        /// becomes `type_`.
+        pub fn apply(&self, name: &RuaName, case: &RuaCase) -> String {
+            if let Some(rename) = self.renames.get(name.render()) {
+                return rename.clone();
+            }
+            let name = name.get_name_with_case(case);
+            if self.reserved.contains(&name) {
+                format!("{}_", name)
+            } else {
+                name
+            }
+        }
     }
 
     /// Represents a name with a case.
@@ -133,6 +465,8 @@ mod rua_name {
         case: RuaCase,
     }
 
+    /// Displays the name along with its case, e.g. `snake_case(foo_bar)`. Use
+    /// [RuaName::render] to get the identifier itself.
     impl Display for RuaName {
         fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
             write!(f, "{}({})", self.case, self.name)
@@ -197,34 +531,80 @@ mod rua_name {
             self.case.check(&self.name)
         }
 
-        /// Converts the name to the case.
+        /// Converts the name to the case. An invalid result, e.g. for `_`,
+        /// which has no letter to put in the case, is only logged. Use
+        /// [RuaName::try_convert] to handle it instead. The result is not
+        /// debug-asserted to be valid, since [RuaCase::check] also rejects
+        /// names such as `foo1` in snake_case, which no conversion can fix.
         pub fn convert(&self, case: RuaCase) -> Self {
             Self::new(case.convert(&self.name), case)
         }
 
+        /// Converts the name to the case, failing if the converted name is
+        /// not valid in the case.
+        pub fn try_convert(
+            &self,
+            case: RuaCase,
+        ) -> Result<Self, ConversionError> {
+            let name = case.convert(&self.name);
+            if !case.check(&name) {
+                return Err(ConversionError::builder()
+                    .source_type(self.case.to_string())
+                    .target_type(case.to_string())
+                    .message(format!(
+                        "{} converted to an invalid name {}",
+                        self, name
+                    ))
+                    .build());
+            }
+            Ok(Self { name, case })
+        }
+
         /// Returns the name.
         pub fn get_name(&self) -> &str {
             &self.name
         }
 
+        /// Returns the identifier as it should appear in generated code, i.e.
+        /// the name in its current case.
+        pub fn render(&self) -> &str {
+            &self.name
+        }
+
+        /// Returns the case of the name.
+        pub fn case(&self) -> &RuaCase {
+            &self.case
+        }
+
         /// Returns the name in the case.
         pub fn get_name_with_case(&self, case: &RuaCase) -> String {
             case.convert(&self.name)
         }
+
+        /// Checks if the names are the same once converted to snake_case,
+        /// e.g. `fooBar`, `foo_bar` and `FooBar`. Use it instead of `==` to
+        /// detect names that would collide once rendered in a single case.
+        pub fn same_identity(&self, other: &RuaName) -> bool {
+            self.get_name_with_case(&RuaCase::SnakeCase)
+                == other.get_name_with_case(&RuaCase::SnakeCase)
+        }
     }
 
     pub use syn_convert::*;
 
     mod syn_convert {
         use proc_macro2::Ident;
+        use syn::ext::IdentExt;
 
         use super::*;
 
+        /// Raw identifiers lose their `r#` prefix, e.g. `r#type` becomes
+        /// `type`, so that generators can sanitize them for their target.
         impl TryFrom<&Ident> for RuaName {
             type Error = ConversionError;
 
             fn try_from(value: &Ident) -> Result<Self, Self::Error> {
-                let name = value.to_string();
+                let name = value.unraw().to_string();
                 let case = if name.is_snake_case() {
                     RuaCase::SnakeCase
                 } else if name.is_camel_case() {
@@ -246,6 +626,36 @@ mod rua_name {
                 Ok(Self { name, case })
             }
         }
+
+        impl RuaName {
+            /// Converts the ident like `TryFrom<&Ident>` does, but never
+            /// fails. The case checks are lenient, so most idents are kept as
+            /// is, e.g. `XMLHttpRequest` and `MAX_LEN` as PascalCase and
+            /// `_internal` as snake_case. The rejected ones start with an
+            /// underscore and have a capital or a digit, e.g. `_Internal` or
+            /// `_MAX`, and are normalized to the best-guess case with a
+            /// warning, keeping the leading underscores: names without a
+            /// lowercase letter and lowercase-first names become snake_case,
+            /// and uppercase-first names become PascalCase.
+            pub fn from_ident_lossy(value: &Ident) -> Self {
+                if let Ok(name) = Self::try_from(value) {
+                    return name;
+                }
+                let name = value.unraw().to_string();
+                let has_lowercase = name.chars().any(is_lower);
+                let starts_uppercase =
+                    name.trim_start_matches('_').starts_with(is_upper);
+                let res = if !has_lowercase {
+                    Self::new(name.to_lowercase(), RuaCase::SnakeCase)
+                } else if starts_uppercase {
+                    Self::new(name.to_pascal_case(), RuaCase::PascalCase)
+                } else {
+                    Self::new(name.to_snake_case(), RuaCase::SnakeCase)
+                };
+                log::warn!("{} is not in a valid case, using {}", name, res);
+                res
+            }
+        }
     }
 }
 
@@ -262,7 +672,9 @@ mod rua_mod {
         name: RuaName,
         /// Represents the type of the module.
         ty: RuaModType,
-        /// Represents the root path of the module.
+        /// Represents the path to the file containing the items of the
+        /// module, i.e. its own file for a crate or file module, or the file
+        /// it is declared in for an inline module, if known.
         root_path: Option<PathBuf>,
         /// Whether if the module is public.
         is_public: bool,
@@ -273,8 +685,10 @@ mod rua_mod {
     pub enum RuaModType {
         /// Represents a crate module.
         CrateModule,
-        /// Represents a file module.
+        /// Represents a file module, i.e. `mod a;`.
         FileModule,
+        /// Represents an inline module, i.e. `mod a { ... }`.
+        InlineModule,
     }
 
     impl RuaNamed for RuaMod {
@@ -305,20 +719,95 @@ mod rua_mod {
             &self.ty
         }
 
-        /// Returns the path to the root of the module.
+        /// Returns the path to the file containing the items of the module.
         pub fn root_path(&self) -> &Option<PathBuf> {
             &self.root_path
         }
+
+        /// Returns whether if the module is public.
+        pub fn is_public(&self) -> bool {
+            self.is_public
+        }
+    }
+
+    /// Represents a module along with its submodules, so that generators can
+    /// mirror the module hierarchy of the crate, e.g. with directories.
+    #[rua_model_derive]
+    pub struct RuaModTree {
+        /// Represents the module.
+        pub module: RuaMod,
+        /// Represents the submodules, in declaration order.
+        pub children: Vec<RuaModTree>,
+    }
+
+    impl RuaModTree {
+        /// Creates a tree with the module and no submodules.
+        pub fn new(module: RuaMod) -> Self {
+            Self {
+                module,
+                children: Vec::new(),
+            }
+        }
+
+        /// Returns the number of levels of modules in the tree, i.e. 1 for a
+        /// module without submodules and 3 for a crate with `a::b`.
+        pub fn depth(&self) -> usize {
+            1 + self
+                .children
+                .iter()
+                .map(|child| child.depth())
+                .max()
+                .unwrap_or(0)
+        }
+
+        /// Returns the submodule at the path relative to this module, e.g.
+        /// `["a", "b"]` for `a::b`, or this module for an empty path.
+        pub fn find(&self, path: &[&str]) -> Option<&RuaModTree> {
+            match path.split_first() {
+                Some((name, rest)) => self
+                    .children
+                    .iter()
+                    .find(|child| child.module.name().render() == *name)?
+                    .find(rest),
+                None => Some(self),
+            }
+        }
+
+        /// Returns the submodule at the path relative to this module, see
+        /// [RuaModTree::find].
+        pub fn find_mut(&mut self, path: &[&str]) -> Option<&mut RuaModTree> {
+            match path.split_first() {
+                Some((name, rest)) => self
+                    .children
+                    .iter_mut()
+                    .find(|child| child.module.name().render() == *name)?
+                    .find_mut(rest),
+                None => Some(self),
+            }
+        }
     }
 
     pub use syn_convert::*;
     mod syn_convert {
+        use std::path::Path;
+
+        use syn::spanned::Spanned;
+
         use super::*;
 
         impl From<syn::ItemMod> for RuaMod {
             fn from(item: syn::ItemMod) -> Self {
+                (&item).into()
+            }
+        }
+
+        impl From<&syn::ItemMod> for RuaMod {
+            fn from(item: &syn::ItemMod) -> Self {
                 let name = item.ident.to_string();
-                let ty = RuaModType::FileModule;
+                let ty = match item.content {
+                    Some(_) => RuaModType::InlineModule,
+                    None => RuaModType::FileModule,
+                };
                 let is_public = match item.vis {
                     syn::Visibility::Public(_) => true,
                     _ => false,
@@ -326,6 +815,142 @@ mod rua_mod {
                 Self::new(name, ty, None, is_public)
             }
         }
+
+        impl RuaModTree {
+            /// Adds the modules declared in the items as submodules, in
+            /// order, recursing into the inline ones, i.e. `mod a { ... }`.
+            /// The ones declared as `mod a;` are added without submodules,
+            /// since their items are in another file; find them with
+            /// [RuaModTree::find_mut] and add the items of that file. With a
+            /// `cfg`, the modules disabled by their `#[cfg(...)]` attributes
+            /// are skipped.
+            ///
+            /// The items must be those of the file of this module. If its
+            /// path is known, the paths of the file modules are derived from
+            /// it like `rustc` does, following `#[path = "..."]`, e.g.
+            /// `src/a/b.rs` for `mod b;` in `mod a { ... }` in `src/lib.rs`.
+            pub fn add_items(
+                &mut self,
+                items: &[syn::Item],
+                cfg: Option<&RuaCfg>,
+            ) -> Result<(), ConversionError> {
+                let file = self.module.root_path.clone();
+                let dir = file.as_deref().map(mod_dir);
+                self.add_items_in(items, cfg, file.as_deref(), dir, false)
+            }
+
+            /// Adds the modules declared in the items, where `file` is the
+            /// file containing them and `dir` the directory where the files
+            /// of the modules they declare are, and `is_inline` whether they
+            /// are in an inline module.
+            fn add_items_in(
+                &mut self,
+                items: &[syn::Item],
+                cfg: Option<&RuaCfg>,
+                file: Option<&Path>,
+                dir: Option<PathBuf>,
+                is_inline: bool,
+            ) -> Result<(), ConversionError> {
+                for item in items.iter() {
+                    let item = match item {
+                        syn::Item::Mod(item) => item,
+                        _ => continue,
+                    };
+                    if let Some(cfg) = cfg {
+                        if !cfg.is_enabled(&item.attrs)? {
+                            continue;
+                        }
+                    }
+                    let mut module = RuaMod::from(item);
+                    // `#[path]` is relative to the directory of the file,
+                    // except in inline modules, where it is relative to
+                    // their directory
+                    let base = if is_inline {
+                        dir.clone()
+                    } else {
+                        file.and_then(Path::parent).map(Path::to_path_buf)
+                    };
+                    let path_attr = mod_path_attr(&item.attrs)?;
+                    let name = module.name.render().to_owned();
+                    let child = match &item.content {
+                        Some((_, items)) => {
+                            module.root_path = file.map(Path::to_path_buf);
+                            let dir = match path_attr {
+                                Some(path) => base.map(|base| base.join(path)),
+                                None => dir.as_ref().map(|dir| dir.join(&name)),
+                            };
+                            let mut child = RuaModTree::new(module);
+                            child.add_items_in(items, cfg, file, dir, true)?;
+                            child
+                        }
+                        None => {
+                            module.root_path = match path_attr {
+                                Some(path) => base.map(|base| base.join(path)),
+                                None => {
+                                    dir.as_ref().map(|dir| mod_file(dir, &name))
+                                }
+                            };
+                            RuaModTree::new(module)
+                        }
+                    };
+                    self.children.push(child);
+                }
+                Ok(())
+            }
+        }
+
+        /// Returns the directory containing the files of the modules
+        /// declared in the file, i.e. its own directory for `lib.rs`,
+        /// `main.rs` and `mod.rs`, or the directory named after it, e.g.
+        /// `src/a` for `src/a.rs`.
+        fn mod_dir(file: &Path) -> PathBuf {
+            let parent = file.parent().unwrap_or_else(|| Path::new(""));
+            match file.file_name().and_then(|name| name.to_str()) {
+                Some("lib.rs" | "main.rs" | "mod.rs") => parent.to_path_buf(),
+                _ => match file.file_stem() {
+                    Some(stem) => parent.join(stem),
+                    None => parent.to_path_buf(),
+                },
+            }
+        }
+
+        /// Returns the file of the module with the name declared in the
+        /// directory, i.e. `a/mod.rs` if it exists, or `a.rs`.
+        fn mod_file(dir: &Path, name: &str) -> PathBuf {
+            let mod_rs = dir.join(name).join("mod.rs");
+            if mod_rs.is_file() {
+                mod_rs
+            } else {
+                dir.join(format!("{}.rs", name))
+            }
+        }
+
+        /// Returns the value of the `#[path = "..."]` attribute, if any.
+        fn mod_path_attr(
+            attrs: &[syn::Attribute],
+        ) -> Result<Option<String>, ConversionError> {
+            let attr =
+                match attrs.iter().find(|attr| attr.path().is_ident("path")) {
+                    Some(attr) => attr,
+                    None => return Ok(None),
+                };
+            match &attr.meta {
+                syn::Meta::NameValue(syn::MetaNameValue {
+                    value:
+                        syn::Expr::Lit(syn::ExprLit {
+                            lit: syn::Lit::Str(lit),
+                            ..
+                        }),
+                    ..
+                }) => Ok(Some(lit.value())),
+                _ => Err(ConversionError::builder()
+                    .span(&attr.span())
+                    .source_type("syn::Attribute")
+                    .target_type("RuaMod")
+                    .message("expected #[path = \"...\"]")
+                    .build()),
+            }
+        }
     }
 }
 
@@ -385,20 +1010,377 @@ mod rua_type {
         Enum(RuaEnum),
         /// Represents a function type.
         Pointer(RuaPointer),
+        /// Represents a non-zero integer type, e.g. [`std::num::NonZeroU32`].
+        NonZero(RuaNonZero),
         /// Represents a reference type.
         Reference(RuaReference),
         /// Represents a function type.
         Fn(RuaFn),
         /// Represents a custom type.
-        Custom(RuaName),
+        Custom(RuaCustom),
         /// Represents a generic type.
         Unit,
     }
 
-    pub use syn_convert::*;
+    impl RuaType {
+        /// Parses the type from Rust source, e.g. `Vec<u8>` or `&mut [i32]`,
+        /// and converts it like any other [syn::Type]. The locations in the
+        /// errors are relative to `s`, see [RuaType::parse_lit] for types
+        /// written in a file.
+        pub fn parse(s: &str) -> Result<Self, ConversionError> {
+            let ty = syn::parse_str::<syn::Type>(s).map_err(|err| {
+                ConversionError::builder()
+                    .span(&err.span())
+                    .source_type("str")
+                    .target_type("RuaType")
+                    .message(format!("invalid type {:?}: {}", s, err))
+                    .build()
+            })?;
+            (&ty).try_into()
+        }
+
+        /// Parses the type from a string literal in a file, e.g. the `"T"` of
+        /// `#[rua(as = "T")]`, like [RuaType::parse]. The errors point at
+        /// the literal in the file instead of at a location in the string.
+        pub fn parse_lit(lit: &syn::LitStr) -> Result<Self, ConversionError> {
+            let ty = lit.parse::<syn::Type>().map_err(|err| {
+                ConversionError::builder()
+                    .span(&lit.span())
+                    .source_type("syn::LitStr")
+                    .target_type("RuaType")
+                    .message(format!("invalid type {:?}: {}", lit.value(), err))
+                    .build()
+            })?;
+            (&ty).try_into()
+        }
+
+        /// Replaces the type parameter `name` with `ty` everywhere in the type,
+        /// e.g. `&[T]` becomes `&[u32]` for `T = u32`.
+        pub fn substitute(&mut self, name: &str, ty: &RuaType) {
+            match self {
+                RuaType::Custom(custom)
+                    if custom.path.is_empty()
+                        && custom.args.is_empty()
+                        && custom.name.render() == name =>
+                {
+                    *self = ty.clone()
+                }
+                RuaType::Custom(custom) => {
+                    for arg in custom.args.iter_mut() {
+                        arg.substitute(name, ty);
+                    }
+                }
+                RuaType::Slice(slice) => slice.ty.substitute(name, ty),
+                RuaType::Array(arr) => arr.ty.substitute(name, ty),
+                RuaType::Tuple(tuple) => {
+                    for elem in tuple.tys.iter_mut() {
+                        elem.substitute(name, ty);
+                    }
+                }
+                RuaType::Pointer(ptr) => ptr.ty.substitute(name, ty),
+                RuaType::Reference(reference) => {
+                    reference.ty.substitute(name, ty)
+                }
+                RuaType::NonZero(non_zero) => non_zero.ty.substitute(name, ty),
+                RuaType::Fn(RuaFn::Bare(f)) => {
+                    for param in f.params.iter_mut() {
+                        param.substitute(name, ty);
+                    }
+                    f.ret.substitute(name, ty);
+                }
+                _ => {}
+            }
+        }
+
+        /// Collapses the references to references anywhere in the type into
+        /// single references, e.g. `&&i32` into `&i32`. The result is mutable
+        /// only if all the collapsed references are, since the value can only
+        /// be mutated through mutable references all the way, e.g. `&mut &i32`
+        /// becomes `&i32`. The lifetime of the outermost reference is kept.
+        pub fn flatten_references(self) -> Self {
+            match self {
+                RuaType::Reference(mut reference) => {
+                    let mut ty = reference.ty.flatten_references();
+                    if let RuaType::Reference(inner) = ty {
+                        reference.is_mut &= inner.is_mut;
+                        ty = *inner.ty;
+                    }
+                    reference.ty = Box::new(ty);
+                    RuaType::Reference(reference)
+                }
+                RuaType::Slice(mut slice) => {
+                    slice.ty = Box::new(slice.ty.flatten_references());
+                    RuaType::Slice(slice)
+                }
+                RuaType::Array(mut arr) => {
+                    arr.ty = Box::new(arr.ty.flatten_references());
+                    RuaType::Array(arr)
+                }
+                RuaType::Tuple(mut tuple) => {
+                    tuple.tys = tuple
+                        .tys
+                        .into_iter()
+                        .map(|ty| ty.flatten_references())
+                        .collect();
+                    RuaType::Tuple(tuple)
+                }
+                RuaType::Pointer(mut ptr) => {
+                    ptr.ty = Box::new(ptr.ty.flatten_references());
+                    RuaType::Pointer(ptr)
+                }
+                RuaType::NonZero(mut non_zero) => {
+                    non_zero.ty = Box::new(non_zero.ty.flatten_references());
+                    RuaType::NonZero(non_zero)
+                }
+                RuaType::Fn(RuaFn::Bare(mut f)) => {
+                    f.params = f
+                        .params
+                        .into_iter()
+                        .map(|ty| ty.flatten_references())
+                        .collect();
+                    f.ret = Box::new(f.ret.flatten_references());
+                    RuaType::Fn(RuaFn::Bare(f))
+                }
+                RuaType::Custom(mut custom) => {
+                    custom.args = custom
+                        .args
+                        .into_iter()
+                        .map(|ty| ty.flatten_references())
+                        .collect();
+                    RuaType::Custom(custom)
+                }
+                ty => ty,
+            }
+        }
+
+        /// Returns the names of the custom types the type refers to, e.g.
+        /// `Foo` and `Bar` for `([Foo; 4], &[Bar])`, in order of appearance
+        /// and without duplicates. The generic arguments of custom types are
+        /// included, e.g. `Vec` and `Foo` for `Vec<Foo>`. Generators can
+        /// import them from there.
+        pub fn dependencies(&self) -> Vec<&RuaName> {
+            let mut deps = Vec::new();
+            self.collect_dependencies(&mut deps);
+            deps
+        }
+
+        /// Checks if the type is a signed or an unsigned integer of any width.
+        pub fn is_integer(&self) -> bool {
+            matches!(
+                self,
+                RuaType::I8
+                    | RuaType::I16
+                    | RuaType::I32
+                    | RuaType::I64
+                    | RuaType::I128
+                    | RuaType::Isize
+                    | RuaType::U8
+                    | RuaType::U16
+                    | RuaType::U32
+                    | RuaType::U64
+                    | RuaType::U128
+                    | RuaType::Usize
+            )
+        }
+
+        /// Checks if the type or any type nested in it satisfies `pred`, e.g.
+        /// `(f64, u8)` contains a float but `(u8, u8)` does not. The
+        /// parameters and the return type of bare functions are included.
+        pub fn contains(&self, pred: impl Fn(&RuaType) -> bool) -> bool {
+            self.contains_dyn(&pred)
+        }
+
+        fn contains_dyn(&self, pred: &dyn Fn(&RuaType) -> bool) -> bool {
+            if pred(self) {
+                return true;
+            }
+            match self {
+                RuaType::Slice(slice) => slice.ty.contains_dyn(pred),
+                RuaType::Array(arr) => arr.ty.contains_dyn(pred),
+                RuaType::Tuple(tuple) => {
+                    tuple.tys.iter().any(|ty| ty.contains_dyn(pred))
+                }
+                RuaType::Pointer(ptr) => ptr.ty.contains_dyn(pred),
+                RuaType::Reference(reference) => {
+                    reference.ty.contains_dyn(pred)
+                }
+                RuaType::NonZero(non_zero) => non_zero.ty.contains_dyn(pred),
+                RuaType::Fn(RuaFn::Bare(f)) => {
+                    f.params.iter().any(|ty| ty.contains_dyn(pred))
+                        || f.ret.contains_dyn(pred)
+                }
+                RuaType::Custom(custom) => {
+                    custom.args.iter().any(|ty| ty.contains_dyn(pred))
+                }
+                _ => false,
+            }
+        }
+
+        /// Reinterprets the custom types named `Char` as `char`, and `Unit` or
+        /// `unit` as `()`, anywhere in the type. Such names convert to
+        /// [RuaType::Custom] since they may be user types, so call this to
+        /// opt into the aliases where they are known not to be.
+        pub fn resolve_aliases(self) -> Self {
+            match self {
+                RuaType::Custom(custom) if custom.is_alias_of("Char") => {
+                    RuaType::Char
+                }
+                RuaType::Custom(custom)
+                    if custom.is_alias_of("Unit")
+                        || custom.is_alias_of("unit") =>
+                {
+                    RuaType::Unit
+                }
+                RuaType::Custom(mut custom) => {
+                    custom.args = custom
+                        .args
+                        .into_iter()
+                        .map(|ty| ty.resolve_aliases())
+                        .collect();
+                    RuaType::Custom(custom)
+                }
+                RuaType::Slice(mut slice) => {
+                    slice.ty = Box::new(slice.ty.resolve_aliases());
+                    RuaType::Slice(slice)
+                }
+                RuaType::Array(mut arr) => {
+                    arr.ty = Box::new(arr.ty.resolve_aliases());
+                    RuaType::Array(arr)
+                }
+                RuaType::Tuple(mut tuple) => {
+                    tuple.tys = tuple
+                        .tys
+                        .into_iter()
+                        .map(|ty| ty.resolve_aliases())
+                        .collect();
+                    RuaType::Tuple(tuple)
+                }
+                RuaType::Pointer(mut ptr) => {
+                    ptr.ty = Box::new(ptr.ty.resolve_aliases());
+                    RuaType::Pointer(ptr)
+                }
+                RuaType::NonZero(mut non_zero) => {
+                    non_zero.ty = Box::new(non_zero.ty.resolve_aliases());
+                    RuaType::NonZero(non_zero)
+                }
+                RuaType::Reference(mut reference) => {
+                    reference.ty = Box::new(reference.ty.resolve_aliases());
+                    RuaType::Reference(reference)
+                }
+                RuaType::Fn(RuaFn::Bare(mut f)) => {
+                    f.params = f
+                        .params
+                        .into_iter()
+                        .map(|ty| ty.resolve_aliases())
+                        .collect();
+                    f.ret = Box::new(f.ret.resolve_aliases());
+                    RuaType::Fn(RuaFn::Bare(f))
+                }
+                ty => ty,
+            }
+        }
+
+        /// Adds the names of the custom types the type refers to to `deps`,
+        /// skipping those already in it.
+        pub(crate) fn collect_dependencies<'a>(
+            &'a self,
+            deps: &mut Vec<&'a RuaName>,
+        ) {
+            let mut customs = Vec::new();
+            self.collect_customs(&mut customs);
+            for custom in customs {
+                if !deps.contains(&&custom.name) {
+                    deps.push(&custom.name);
+                }
+            }
+        }
+
+        /// Adds the custom types the type refers to to `customs`, in order of
+        /// appearance, including the generic arguments of custom types.
+        pub(crate) fn collect_customs<'a>(
+            &'a self,
+            customs: &mut Vec<&'a RuaCustom>,
+        ) {
+            match self {
+                RuaType::Custom(custom) => {
+                    customs.push(custom);
+                    for arg in custom.args.iter() {
+                        arg.collect_customs(customs);
+                    }
+                }
+                RuaType::Slice(slice) => slice.ty.collect_customs(customs),
+                RuaType::Array(arr) => arr.ty.collect_customs(customs),
+                RuaType::Tuple(tuple) => {
+                    for ty in tuple.tys.iter() {
+                        ty.collect_customs(customs);
+                    }
+                }
+                RuaType::Pointer(ptr) => ptr.ty.collect_customs(customs),
+                RuaType::Reference(reference) => {
+                    reference.ty.collect_customs(customs)
+                }
+                RuaType::NonZero(non_zero) => {
+                    non_zero.ty.collect_customs(customs)
+                }
+                RuaType::Fn(RuaFn::Bare(f)) => {
+                    for param in f.params.iter() {
+                        param.collect_customs(customs);
+                    }
+                    f.ret.collect_customs(customs);
+                }
+                _ => {}
+            }
+        }
+
+        /// Returns the size and the alignment of the type in bytes, or `None`
+        /// if they depend on the target platform or are unknown, e.g. for
+        /// pointers and user-defined types. `u64` and `f64` are assumed to be
+        /// 8-byte aligned as on 64-bit targets. Fails if the size of an array
+        /// overflows `usize`.
+        pub fn size_and_align(
+            &self,
+        ) -> Result<Option<(usize, usize)>, ConversionError> {
+            let layout = match self {
+                RuaType::I8 | RuaType::U8 | RuaType::Bool => Some((1, 1)),
+                RuaType::I16 | RuaType::U16 => Some((2, 2)),
+                RuaType::I32 | RuaType::U32 | RuaType::F32 | RuaType::Char => {
+                    Some((4, 4))
+                }
+                RuaType::I64 | RuaType::U64 | RuaType::F64 => Some((8, 8)),
+                RuaType::Unit => Some((0, 1)),
+                RuaType::NonZero(non_zero) => non_zero.ty.size_and_align()?,
+                RuaType::Array(RuaArray {
+                    ty,
+                    len: RuaArrayLen::Num(len),
+                }) => match ty.size_and_align()? {
+                    Some((size, align)) => {
+                        let size = size.checked_mul(*len).ok_or_else(|| {
+                            ConversionError::builder()
+                                .source_type("RuaType")
+                                .target_type("size")
+                                .message(format!(
+                                    "the size of {} overflows usize",
+                                    self
+                                ))
+                                .build()
+                        })?;
+                        Some((size, align))
+                    }
+                    None => None,
+                },
+                _ => None,
+            };
+            Ok(layout)
+        }
+    }
+
+    pub(crate) use syn_convert::*;
     mod syn_convert {
         use proc_macro2::Ident;
-        use syn::{spanned::Spanned, BareFnArg, ReturnType, Type, TypePath};
+        use syn::{
+            spanned::Spanned, BareFnArg, GenericArgument, Path, PathArguments,
+            PathSegment, ReturnType, Type, TypePath,
+        };
 
         use super::*;
 
@@ -476,6 +1458,10 @@ mod rua_type {
                     Type::TraitObject(_) => {
                         generate_error("unsupported type TraitObject")
                     }
+                    // `()` is the unit type rather than an empty tuple
+                    Type::Tuple(tuple_item) if tuple_item.elems.is_empty() => {
+                        Ok(RuaType::Unit)
+                    }
                     Type::Tuple(tuple_item) => {
                         tuple_item.try_into()
                             .map_err(err_mapper)
@@ -528,6 +1514,52 @@ mod rua_type {
             }
         }
 
+        /// Returns the first generic type argument of the segment, skipping
+        /// lifetimes and consts.
+        fn first_generic_type(segment: &PathSegment) -> Option<&Type> {
+            generic_types(segment).next()
+        }
+
+        /// Returns the generic type arguments of the path segment, skipping
+        /// lifetimes and const arguments.
+        fn generic_types(
+            segment: &PathSegment,
+        ) -> impl Iterator<Item = &Type> + '_ {
+            let args = match &segment.arguments {
+                PathArguments::AngleBracketed(args) => Some(&args.args),
+                _ => None,
+            };
+            args.into_iter().flatten().filter_map(|arg| match arg {
+                GenericArgument::Type(ty) => Some(ty),
+                _ => None,
+            })
+        }
+
+        /// Types other than the primitives convert to [RuaType::Custom] with
+        /// their path and their generic type arguments, e.g. `Vec<u8>` keeps
+        /// `u8`, so they compose inside [RuaTuple], [RuaArray] and [RuaSlice].
+        ///
+        /// The exceptions are:
+        /// - `Cow<'a, T>` is treated as transparent and converts to `T`,
+        ///   dropping the lifetime.
+        /// - `NonNull<T>` converts to a non-null mutable [RuaPointer].
+        /// - `NonZeroU32` and `NonZero<u32>` (and the other integer widths)
+        ///   convert to a [RuaNonZero]. `NonZero<T>` fails unless `T` is an
+        ///   integer.
+        ///
+        /// They are recognized unqualified, as when imported, or qualified
+        /// with their module in `std`, `core` or `alloc`, e.g.
+        /// `std::borrow::Cow`, so that `my::Cow` stays a [RuaType::Custom].
+        ///
+        /// Qualified paths to the primitives, such as `std::primitive::u32`
+        /// and `core::primitive::bool`, convert to the primitive and fail if
+        /// the last segment is not one, and `String` is recognized as
+        /// `std::string::String` or `alloc::string::String`. Other paths
+        /// ending with the name of a primitive or `String`, e.g. `my::u8`,
+        /// convert to [RuaType::Custom]. Standard library types without a
+        /// dedicated variant, such as `CString`, `CStr`, `OsString` and
+        /// `PathBuf`, convert to [RuaType::Custom] with their own path, so
+        /// generators can map them by name.
         impl TryFrom<&TypePath> for RuaType {
             type Error = ConversionError;
 
@@ -539,6 +1571,25 @@ mod rua_type {
                         .target_type("RuaType")
                         .build()
                 };
+                if value.qself.is_some() {
+                    let assoc = value
+                        .path
+                        .segments
+                        .last()
+                        .map(|segment| segment.ident.to_string())
+                        .unwrap_or_default();
+                    return Err(ConversionError::builder()
+                        .span(&value.span())
+                        .source_type("syn::TypePath")
+                        .target_type("RuaType")
+                        .message(format!(
+                            "unsupported qualified path to {}, associated \
+                             types such as <T as Trait>::Item cannot be \
+                             converted",
+                            assoc
+                        ))
+                        .build());
+                }
                 let path = &value.path;
                 let segments = &path.segments;
                 // find the last segment
@@ -549,10 +1600,150 @@ mod rua_type {
                             .build(),
                     )
                 })?;
-                (&last_segment.ident).try_into().map_err(err_mapper)
+                let std_ident = |module: &str| {
+                    std_segment(path, module).map(|segment| &segment.ident)
+                };
+                if std_ident("borrow").is_some_and(|ident| ident == "Cow") {
+                    let inner =
+                        first_generic_type(last_segment).ok_or_else(|| {
+                            err_mapper(
+                                ConversionError::builder()
+                                    .span(&last_segment.span())
+                                    .message("Cow without a type argument")
+                                    .build(),
+                            )
+                        })?;
+                    return inner.try_into().map_err(err_mapper);
+                }
+                if std_ident("ptr").is_some_and(|ident| ident == "NonNull") {
+                    let inner =
+                        first_generic_type(last_segment).ok_or_else(|| {
+                            err_mapper(
+                                ConversionError::builder()
+                                    .span(&last_segment.span())
+                                    .message("NonNull without a type argument")
+                                    .build(),
+                            )
+                        })?;
+                    return Ok(RuaType::Pointer(RuaPointer {
+                        is_const: false,
+                        is_non_null: true,
+                        ty: Box::new(inner.try_into().map_err(err_mapper)?),
+                    }));
+                }
+                if std_ident("num").is_some_and(|ident| ident == "NonZero") {
+                    let inner =
+                        first_generic_type(last_segment).ok_or_else(|| {
+                            err_mapper(
+                                ConversionError::builder()
+                                    .span(&last_segment.span())
+                                    .message("NonZero without a type argument")
+                                    .build(),
+                            )
+                        })?;
+                    let ty: RuaType = inner.try_into().map_err(err_mapper)?;
+                    if !ty.is_integer() {
+                        return Err(err_mapper(
+                            ConversionError::builder()
+                                .span(&inner.span())
+                                .message(format!(
+                                    "NonZero of {}, which is not an integer",
+                                    ty
+                                ))
+                                .build(),
+                        ));
+                    }
+                    return Ok(RuaType::NonZero(RuaNonZero {
+                        ty: Box::new(ty),
+                    }));
+                }
+                if let Some(non_zero) = std_ident("num").and_then(|ident| {
+                    ident
+                        .to_string()
+                        .strip_prefix("NonZero")
+                        .and_then(RuaNonZero::from_suffix)
+                }) {
+                    return Ok(RuaType::NonZero(non_zero));
+                }
+                let is_builtin = (segments.len() == 1
+                    && path.leading_colon.is_none())
+                    || is_primitive_module(path)
+                    || std_ident("string")
+                        .is_some_and(|ident| ident == "String");
+                let ty: RuaType = if is_builtin {
+                    (&last_segment.ident).try_into().map_err(err_mapper)?
+                } else {
+                    RuaType::Custom(RuaCustom::new(
+                        (&last_segment.ident).try_into().map_err(err_mapper)?,
+                    ))
+                };
+                match ty {
+                    RuaType::Custom(custom) if is_primitive_module(path) => {
+                        Err(err_mapper(
+                            ConversionError::builder()
+                                .span(&last_segment.span())
+                                .message(format!(
+                                    "{} is not a primitive type",
+                                    custom.name.render()
+                                ))
+                                .build(),
+                        ))
+                    }
+                    RuaType::Custom(mut custom) => {
+                        custom.path = segments
+                            .iter()
+                            .take(segments.len() - 1)
+                            .map(|segment| segment.ident.to_string())
+                            .collect();
+                        custom.args = generic_types(last_segment)
+                            .map(|ty| ty.try_into())
+                            .collect::<Result<_, _>>()
+                            .map_err(err_mapper)?;
+                        Ok(RuaType::Custom(custom))
+                    }
+                    ty => Ok(ty),
+                }
             }
         }
 
+        /// Returns the last segment of the path if it may name a standard
+        /// library type from `module`, i.e. if the path is a single segment,
+        /// as when the type is imported, or is qualified with `std`, `core` or
+        /// `alloc` and `module`, e.g. `std::borrow::Cow` for `borrow`. Paths
+        /// into other crates or modules, e.g. `my::Cow`, are not.
+        pub(crate) fn std_segment<'a>(
+            path: &'a Path,
+            module: &str,
+        ) -> Option<&'a PathSegment> {
+            let segments = path.segments.iter().collect::<Vec<_>>();
+            match segments.as_slice() {
+                [name] if path.leading_colon.is_none() => Some(name),
+                [krate, std_module, name]
+                    if (krate.ident == "std"
+                        || krate.ident == "core"
+                        || krate.ident == "alloc")
+                        && std_module.ident == module =>
+                {
+                    Some(name)
+                }
+                _ => None,
+            }
+        }
+
+        /// Checks if the path is in `std::primitive` or `core::primitive`.
+        fn is_primitive_module(path: &Path) -> bool {
+            let segments = path
+                .segments
+                .iter()
+                .map(|segment| segment.ident.to_string())
+                .collect::<Vec<_>>();
+            matches!(
+                segments.as_slice(),
+                [krate, module, _] if (krate == "std" || krate == "core")
+                    && module == "primitive"
+            )
+        }
+
         impl TryFrom<&Ident> for RuaType {
             type Error = ConversionError;
 
@@ -594,17 +1785,16 @@ mod rua_type {
                     Ok(RuaType::F64)
                 } else if value.eq("bool") {
                     Ok(RuaType::Bool)
-                } else if value.eq("char") || value.eq("Char") {
+                } else if value.eq("char") {
                     Ok(RuaType::Char)
                 } else if value.eq("str") {
                     Ok(RuaType::Str)
                 } else if value.eq("String") {
                     Ok(RuaType::String)
-                } else if value.eq("unit") || value.eq("Unit") || value.eq("()")
-                {
-                    Ok(RuaType::Unit)
                 } else {
-                    Ok(RuaType::Custom(value.try_into().map_err(err_mapper)?))
+                    Ok(RuaType::Custom(RuaCustom::new(
+                        value.try_into().map_err(err_mapper)?,
+                    )))
                 }
             }
         }
@@ -621,8 +1811,18 @@ mod rua_var {
     pub struct RuaVar {
         /// Represents the name of the variable.
         pub name: RuaName,
-        /// Represents the type of the variable.
+        /// Represents the type of the variable, or the type given by
+        /// `#[rua(as = "T")]` on a field instead of its own.
         pub ty: Box<RuaType>,
+        /// Represents the default value of the variable as a Rust expression,
+        /// given by `#[rua(default = "expr")]` on a field.
+        pub default: Option<String>,
+    }
+
+    impl RuaNamed for RuaVar {
+        fn name(&self) -> &RuaName {
+            &self.name
+        }
     }
 
     pub use syn_convert::*;
@@ -693,6 +1893,7 @@ mod rua_var {
                 Ok(RuaVar {
                     name: var_name,
                     ty: Box::new(var_ty),
+                    default: None,
                 })
             }
         }
@@ -723,10 +1924,22 @@ mod rua_var {
                     }
                     None => return generate_error("field name is required"),
                 };
-                let var_ty = (&value.ty).try_into().map_err(error_mapper)?;
+                let args = rua_attr_args(&value.attrs).map_err(error_mapper)?;
+                // `#[rua(as = "T")]` replaces the type of the field, e.g.
+                // when it cannot be converted
+                let var_ty =
+                    match rua_attr_lit(&args, "as").map_err(error_mapper)? {
+                        Some(ty) => {
+                            RuaType::parse_lit(&ty).map_err(error_mapper)?
+                        }
+                        None => (&value.ty).try_into().map_err(error_mapper)?,
+                    };
+                let default =
+                    rua_attr_str(&args, "default").map_err(error_mapper)?;
                 Ok(RuaVar {
                     name: var_name,
                     ty: Box::new(var_ty),
+                    default,
                 })
             }
         }
@@ -743,6 +1956,24 @@ mod rua_slice {
         pub ty: Box<RuaType>,
     }
 
+    impl RuaSlice {
+        /// Creates a slice of the element type.
+        pub fn new(ty: RuaType) -> Self {
+            Self { ty: Box::new(ty) }
+        }
+
+        /// Returns the element type of the slice.
+        pub fn element(&self) -> &RuaType {
+            &self.ty
+        }
+    }
+
+    impl From<RuaType> for RuaSlice {
+        fn from(value: RuaType) -> Self {
+            Self::new(value)
+        }
+    }
+
     impl From<RuaSlice> for RuaType {
         fn from(value: RuaSlice) -> Self {
             RuaType::Slice(value)
@@ -791,10 +2022,26 @@ mod rua_array {
     pub enum RuaArrayLen {
         /// Represents a constant length.
         Num(usize),
-        /// Represents a variable length. The String is the name of the variable.
+        /// Represents a variable length. The String is the path to the
+        /// constant as written, e.g. `N`, `Self::LEN` or `consts::SIZE`.
         Const(String),
     }
 
+    impl RuaArray {
+        /// Creates an array of the element type with the length.
+        pub fn new(ty: RuaType, len: RuaArrayLen) -> Self {
+            Self {
+                ty: Box::new(ty),
+                len,
+            }
+        }
+
+        /// Returns the element type of the array.
+        pub fn element(&self) -> &RuaType {
+            &self.ty
+        }
+    }
+
     impl From<RuaArray> for RuaType {
         fn from(value: RuaArray) -> Self {
             RuaType::Array(value)
@@ -851,15 +2098,18 @@ mod rua_array {
                         _ => generate_error("unsupported literal type"),
                     },
                     Expr::Path(ref path) => {
-                        let path_segments = &path.path.segments;
-                        if path_segments.len() != 1 {
+                        if path.qself.is_some() {
                             return generate_error(
-                                "unsupported path segments length",
+                                "unsupported qualified path as array length",
                             );
                         }
-                        let path_segment = &path_segments[0];
-                        let ident = &path_segment.ident;
-                        Ok(RuaArrayLen::Const(ident.to_string()))
+                        let segments = path
+                            .path
+                            .segments
+                            .iter()
+                            .map(|segment| segment.ident.to_string())
+                            .collect::<Vec<_>>();
+                        Ok(RuaArrayLen::Const(segments.join("::")))
                     }
                     _ => generate_error("unsupported expression type"),
                 }
@@ -912,6 +2162,106 @@ mod rua_tuple {
     }
 }
 
+pub use rua_generics::*;
+mod rua_generics {
+    use super::*;
+
+    /// Represents a generic parameter of a struct or an enum. Lifetimes carry
+    /// no data, so they are not represented.
+    #[rua_model_derive]
+    pub enum RuaGenericParam {
+        /// Represents a type parameter, i.e. `T`.
+        Type {
+            /// Represents the name of the parameter.
+            name: String,
+        },
+        /// Represents a const parameter, i.e. `const N: usize`.
+        Const {
+            /// Represents the name of the parameter.
+            name: String,
+            /// Represents the type of the parameter.
+            ty: RuaType,
+        },
+    }
+
+    impl RuaGenericParam {
+        /// Returns the name of the parameter.
+        pub fn name(&self) -> &str {
+            match self {
+                RuaGenericParam::Type { name } => name,
+                RuaGenericParam::Const { name, .. } => name,
+            }
+        }
+    }
+
+    impl RuaArrayLen {
+        /// Returns the const parameter the length refers to, if the length
+        /// is [RuaArrayLen::Const] and the parameter is declared in `params`.
+        pub fn const_param<'a>(
+            &self,
+            params: &'a [RuaGenericParam],
+        ) -> Option<&'a RuaGenericParam> {
+            match self {
+                RuaArrayLen::Const(len) => params.iter().find(|param| {
+                    matches!(
+                        param,
+                        RuaGenericParam::Const { name, .. } if name == len
+                    )
+                }),
+                RuaArrayLen::Num(_) => None,
+            }
+        }
+    }
+
+    /// Converts the generic parameters, skipping lifetimes.
+    pub(crate) fn convert_generics(
+        generics: &syn::Generics,
+    ) -> Result<Vec<RuaGenericParam>, ConversionError> {
+        generics
+            .params
+            .iter()
+            .filter_map(|param| match param {
+                syn::GenericParam::Lifetime(_) => None,
+                syn::GenericParam::Type(ty) => Some(ty.try_into()),
+                syn::GenericParam::Const(konst) => Some(konst.try_into()),
+            })
+            .collect()
+    }
+
+    mod syn_convert {
+        use super::*;
+        use syn::{spanned::Spanned, ConstParam, TypeParam};
+
+        impl TryFrom<&TypeParam> for RuaGenericParam {
+            type Error = ConversionError;
+
+            fn try_from(value: &TypeParam) -> Result<Self, Self::Error> {
+                Ok(RuaGenericParam::Type {
+                    name: value.ident.to_string(),
+                })
+            }
+        }
+
+        impl TryFrom<&ConstParam> for RuaGenericParam {
+            type Error = ConversionError;
+
+            fn try_from(value: &ConstParam) -> Result<Self, Self::Error> {
+                let error_mapper = |err: ConversionError| {
+                    err.builder_for_next()
+                        .span(&value.span())
+                        .source_type("syn::ConstParam")
+                        .target_type("RuaGenericParam")
+                        .build()
+                };
+                Ok(RuaGenericParam::Const {
+                    name: value.ident.to_string(),
+                    ty: (&value.ty).try_into().map_err(error_mapper)?,
+                })
+            }
+        }
+    }
+}
+
 pub use rua_struct::*;
 mod rua_struct {
     use super::*;
@@ -932,8 +2282,16 @@ mod rua_struct {
     pub struct RuaNamedStruct {
         /// Represents the name of the struct.
         pub name: RuaName,
-        /// Represents the fields of the struct.
+        /// Represents the generic parameters of the struct. Always empty for
+        /// enum variants, whose parameters belong to the enum.
+        pub generics: Vec<RuaGenericParam>,
+        /// Represents the fields of the struct, in declaration order.
         pub fields: Vec<RuaVar>,
+        /// Represents if the struct is marked `#[non_exhaustive]`, i.e. more
+        /// fields may be added later.
+        pub is_non_exhaustive: bool,
+        /// Represents the doc comments of the struct or the variant.
+        pub docs: Option<String>,
     }
 
     /// Represents a tuple struct in Rust.
@@ -941,8 +2299,16 @@ mod rua_struct {
     pub struct RuaTupleStruct {
         /// Represents the name of the struct.
         pub name: RuaName,
-        /// Represents the types of the struct.
+        /// Represents the generic parameters of the struct. Always empty for
+        /// enum variants, whose parameters belong to the enum.
+        pub generics: Vec<RuaGenericParam>,
+        /// Represents the types of the struct, in declaration order.
         pub tys: Vec<RuaType>,
+        /// Represents if the struct is marked `#[rua(transparent)]`, i.e. it
+        /// is a newtype that generators should replace with its only field.
+        pub is_transparent: bool,
+        /// Represents the doc comments of the struct or the variant.
+        pub docs: Option<String>,
     }
 
     /// Represents a unit struct in Rust.
@@ -950,6 +2316,40 @@ mod rua_struct {
     pub struct RuaUnitStruct {
         /// Represents the name of the struct.
         pub name: RuaName,
+        /// Represents the generic parameters of the struct. Always empty for
+        /// enum variants, whose parameters belong to the enum.
+        pub generics: Vec<RuaGenericParam>,
+        /// Represents the doc comments of the struct or the variant.
+        pub docs: Option<String>,
+    }
+
+    impl RuaNamedStruct {
+        /// Returns the fields in declaration order. Positional constructors
+        /// and C layouts rely on this order, so it must be kept when the
+        /// fields are converted or stored.
+        pub fn fields_in_order(&self) -> &[RuaVar] {
+            &self.fields
+        }
+
+        /// Returns the field whose name in snake case is `name`, e.g. `x_pos`
+        /// for a field written `x_pos` or `xPos`.
+        pub fn field(&self, name: &str) -> Option<&RuaVar> {
+            self.fields.iter().find(|field| {
+                field.name.get_name_with_case(&RuaCase::SnakeCase) == name
+            })
+        }
+    }
+
+    impl RuaTupleStruct {
+        /// Returns the type of the only field if the struct is marked
+        /// `#[rua(transparent)]`, i.e. the type generators should use in
+        /// place of the struct.
+        pub fn transparent_inner(&self) -> Option<&RuaType> {
+            match self.tys.as_slice() {
+                [inner] if self.is_transparent => Some(inner),
+                _ => None,
+            }
+        }
     }
 
     impl RuaNamed for RuaNamedStruct {
@@ -970,6 +2370,43 @@ mod rua_struct {
         }
     }
 
+    impl RuaStruct {
+        /// Returns the names of the custom types the fields refer to, see
+        /// [RuaType::dependencies]. The generic parameters of the struct are
+        /// not included.
+        pub fn dependencies(&self) -> Vec<&RuaName> {
+            let mut deps = Vec::new();
+            let generics = match self {
+                RuaStruct::Named(named) => {
+                    for field in named.fields.iter() {
+                        field.ty.collect_dependencies(&mut deps);
+                    }
+                    &named.generics
+                }
+                RuaStruct::Tuple(tuple) => {
+                    for ty in tuple.tys.iter() {
+                        ty.collect_dependencies(&mut deps);
+                    }
+                    &tuple.generics
+                }
+                RuaStruct::Unit(unit) => &unit.generics,
+            };
+            deps.retain(|dep| {
+                !generics.iter().any(|param| param.name() == dep.render())
+            });
+            deps
+        }
+
+        /// Returns the doc comments of the struct or the variant.
+        pub fn docs(&self) -> Option<&str> {
+            match self {
+                RuaStruct::Named(named) => named.docs.as_deref(),
+                RuaStruct::Tuple(tuple) => tuple.docs.as_deref(),
+                RuaStruct::Unit(unit) => unit.docs.as_deref(),
+            }
+        }
+    }
+
     impl RuaNamed for RuaStruct {
         fn name(&self) -> &RuaName {
             match self {
@@ -1008,12 +2445,27 @@ mod rua_struct {
     mod syn_convert {
         use proc_macro2::Ident;
         use syn::{
-            spanned::Spanned, Fields, FieldsNamed, FieldsUnnamed, ItemStruct,
-            Variant,
+            spanned::Spanned, Attribute, Fields, FieldsNamed, FieldsUnnamed,
+            ItemStruct, Type, Variant,
         };
 
         use super::*;
 
+        /// Checks if the type is a `PhantomData`, unqualified or qualified as
+        /// `std::marker::PhantomData`. Such fields carry no data, so they are
+        /// dropped from the converted named structs. Tuple structs keep them,
+        /// since dropping them would shift the indices of the other fields.
+        fn is_phantom_data(ty: &Type) -> bool {
+            match ty {
+                Type::Path(path) if path.qself.is_none() => {
+                    std_segment(&path.path, "marker")
+                        .is_some_and(|name| name.ident == "PhantomData")
+                }
+                _ => false,
+            }
+        }
+
+        /// Converts the fields, keeping their declaration order.
         fn convert_named_fields(
             fields: &FieldsNamed,
             error_mapper: &impl Fn(ConversionError) -> ConversionError,
@@ -1021,6 +2473,7 @@ mod rua_struct {
             let fields = fields
                 .named
                 .iter()
+                .filter(|field| !is_phantom_data(&field.ty))
                 .map(|field| field.try_into().map_err(error_mapper))
                 .collect::<Result<Vec<_>, _>>()?;
             Ok(fields)
@@ -1040,26 +2493,57 @@ mod rua_struct {
 
         fn convert_fields(
             name: &Ident,
+            generics: Vec<RuaGenericParam>,
             fields: &Fields,
+            attrs: &[Attribute],
             error_mapper: &impl Fn(ConversionError) -> ConversionError,
         ) -> Result<RuaStruct, ConversionError> {
+            let args = rua_attr_args(attrs).map_err(error_mapper)?;
+            let is_transparent = rua_attr_flag(&args, "transparent");
+            let tys = match fields {
+                Fields::Unnamed(unnamed) => {
+                    Some(convert_unnamed_fields(unnamed, error_mapper)?)
+                }
+                _ => None,
+            };
+            let is_newtype = matches!(&tys, Some(tys) if tys.len() == 1);
+            if is_transparent && !is_newtype {
+                return Err(error_mapper(
+                    ConversionError::builder()
+                        .span(&fields.span())
+                        .source_type("syn::Fields")
+                        .target_type("RuaStruct")
+                        .message(
+                            "#[rua(transparent)] requires exactly one unnamed \
+                             field",
+                        )
+                        .build(),
+                ));
+            }
             match fields {
                 syn::Fields::Named(named) => {
                     let fields = convert_named_fields(named, error_mapper)?;
                     Ok(RuaStruct::Named(RuaNamedStruct {
                         name: name.try_into().map_err(error_mapper)?,
+                        generics,
                         fields,
+                        is_non_exhaustive: is_non_exhaustive(attrs),
+                        docs: doc_comments(attrs),
                     }))
                 }
-                syn::Fields::Unnamed(unnamed) => {
-                    let tys = convert_unnamed_fields(unnamed, error_mapper)?;
+                syn::Fields::Unnamed(_) => {
                     Ok(RuaStruct::Tuple(RuaTupleStruct {
                         name: name.try_into().map_err(error_mapper)?,
-                        tys,
+                        generics,
+                        tys: tys.unwrap_or_default(),
+                        is_transparent,
+                        docs: doc_comments(attrs),
                     }))
                 }
                 syn::Fields::Unit => Ok(RuaStruct::Unit(RuaUnitStruct {
                     name: name.try_into().map_err(error_mapper)?,
+                    generics,
+                    docs: doc_comments(attrs),
                 })),
             }
         }
@@ -1075,7 +2559,15 @@ mod rua_struct {
                         .target_type("RuaStruct")
                         .build()
                 };
-                convert_fields(&value.ident, &value.fields, &error_mapper)
+                let generics =
+                    convert_generics(&value.generics).map_err(error_mapper)?;
+                convert_fields(
+                    &value.ident,
+                    generics,
+                    &value.fields,
+                    &value.attrs,
+                    &error_mapper,
+                )
             }
         }
 
@@ -1090,7 +2582,30 @@ mod rua_struct {
                         .target_type("RuaStruct")
                         .build()
                 };
-                convert_fields(&value.ident, &value.fields, &error_mapper)
+                // discriminants are not part of the models, so one on a
+                // variant with fields would change the layout unnoticed
+                if let Some((_, discriminant)) = &value.discriminant {
+                    if !matches!(value.fields, Fields::Unit) {
+                        return Err(ConversionError::builder()
+                            .span(&discriminant.span())
+                            .source_type("syn::Variant")
+                            .target_type("RuaStruct")
+                            .message(format!(
+                                "variant {} has fields and a discriminant, \
+                                 discriminants are only supported on unit \
+                                 variants",
+                                value.ident
+                            ))
+                            .build());
+                    }
+                }
+                convert_fields(
+                    &value.ident,
+                    Vec::new(),
+                    &value.fields,
+                    &value.attrs,
+                    &error_mapper,
+                )
             }
         }
     }
@@ -1106,8 +2621,28 @@ mod rua_enum {
     pub struct RuaEnum {
         /// Represents the name of the enum.
         pub name: RuaName,
+        /// Represents the generic parameters of the enum.
+        pub generics: Vec<RuaGenericParam>,
         /// Represents the variants of the enum.
         pub variants: Vec<RuaStruct>,
+        /// Represents if the enum is marked `#[non_exhaustive]`, i.e. more
+        /// variants may be added later, so generated matches over it need a
+        /// default case.
+        pub is_non_exhaustive: bool,
+        /// Represents the doc comments of the enum. Those of the variants are
+        /// kept on the variants.
+        pub docs: Option<String>,
+    }
+
+    impl RuaEnum {
+        /// Checks if the enum carries no data, i.e. all of its variants are
+        /// unit variants. Such enums map to plain enums, the others to
+        /// tagged unions.
+        pub fn is_c_like(&self) -> bool {
+            self.variants
+                .iter()
+                .all(|variant| matches!(variant, RuaStruct::Unit(_)))
+        }
     }
 
     impl RuaNamed for RuaEnum {
@@ -1146,6 +2681,10 @@ mod rua_enum {
                     .collect::<Result<Vec<_>, _>>()?;
                 Ok(RuaEnum {
                     name: (&value.ident).try_into().map_err(error_mapper)?,
+                    generics: convert_generics(&value.generics)
+                        .map_err(error_mapper)?,
+                    is_non_exhaustive: is_non_exhaustive(&value.attrs),
+                    docs: doc_comments(&value.attrs),
                     variants,
                 })
             }
@@ -1181,12 +2720,111 @@ mod rua_fn {
     pub struct RuaSigFn {
         /// Represents the name of the function.
         pub name: RuaName,
-        /// Represents the parameters of the function.
+        /// Represents the generic parameters of the function that have not
+        /// been substituted by `#[rua(monomorphize(T = "u32"))]`.
+        pub generics: Vec<RuaGenericParam>,
+        /// Represents the type of the `self` parameter if the function is a
+        /// method, e.g. `&Self` for `&self`.
+        pub receiver: Option<Box<RuaType>>,
+        /// Represents the parameters of the function in declaration order,
+        /// excluding `self`. Parameters without a name, e.g. `_: i32`, are
+        /// named after their position, i.e. `arg0` for the first one.
         pub params: Vec<RuaVar>,
         /// Represents the return type of the function.
         pub ret: Box<RuaType>,
     }
 
+    impl RuaSigFn {
+        /// Substitutes the type parameter `name` with `ty` in the signature
+        /// and removes it from the generics. Returns `false` if the function
+        /// has no such type parameter.
+        pub fn monomorphize(&mut self, name: &str, ty: &RuaType) -> bool {
+            let pos = self.generics.iter().position(|param| {
+                matches!(param, RuaGenericParam::Type { name: n } if n == name)
+            });
+            match pos {
+                Some(pos) => self.generics.remove(pos),
+                None => return false,
+            };
+            if let Some(receiver) = self.receiver.as_mut() {
+                receiver.substitute(name, ty);
+            }
+            for param in self.params.iter_mut() {
+                param.ty.substitute(name, ty);
+            }
+            self.ret.substitute(name, ty);
+            true
+        }
+
+        /// Replaces `Self` in the signature with the type the method belongs
+        /// to, e.g. `fn clone(&self) -> Self` in `impl Foo` becomes
+        /// `fn clone(self: &Foo) -> Foo`.
+        pub fn resolve_self(&mut self, self_ty: &RuaType) {
+            if let Some(receiver) = self.receiver.as_mut() {
+                receiver.substitute("Self", self_ty);
+            }
+            for param in self.params.iter_mut() {
+                param.ty.substitute("Self", self_ty);
+            }
+            self.ret.substitute("Self", self_ty);
+        }
+
+        /// Returns the names and the types of the parameters in declaration
+        /// order, excluding `self`.
+        pub fn named_params(
+            &self,
+        ) -> impl Iterator<Item = (&RuaName, &RuaType)> + '_ {
+            self.params
+                .iter()
+                .map(|param| (&param.name, param.ty.as_ref()))
+        }
+
+        /// Checks if the function still has type parameters, i.e. it cannot
+        /// be exported as is.
+        pub fn is_generic(&self) -> bool {
+            self.generics
+                .iter()
+                .any(|param| matches!(param, RuaGenericParam::Type { .. }))
+        }
+    }
+
+    /// Checks if the function is still generic once its hints are applied,
+    /// warning that it is skipped if so.
+    pub(crate) fn skip_generic(f: &RuaSigFn) -> bool {
+        if f.is_generic() {
+            log::warn!(
+                "skipping generic function {}, add \
+                 #[rua(monomorphize(T = \"...\"))] to export it",
+                f.name.render()
+            );
+        }
+        f.is_generic()
+    }
+
+    /// Applies the `#[rua(monomorphize(T = "u32", ...))]` hints of the
+    /// function to its signature.
+    pub(crate) fn apply_monomorphize_hints(
+        f: &mut RuaSigFn,
+        attrs: &[syn::Attribute],
+    ) -> Result<(), ConversionError> {
+        let args = rua_attr_args(attrs)?;
+        for (name, ty) in rua_attr_pairs(&args, "monomorphize")? {
+            let ty = RuaType::parse_lit(&ty)?;
+            if !f.monomorphize(&name, &ty) {
+                return Err(ConversionError::builder()
+                    .source_type("syn::Attribute")
+                    .target_type("RuaSigFn")
+                    .message(format!(
+                        "{} is not a type parameter of {}",
+                        name,
+                        f.name.render()
+                    ))
+                    .build());
+            }
+        }
+        Ok(())
+    }
+
     impl RuaNamed for RuaSigFn {
         fn name(&self) -> &RuaName {
             &self.name
@@ -1214,7 +2852,11 @@ mod rua_fn {
     pub use syn_convert::*;
 
     mod syn_convert {
-        use syn::{spanned::Spanned, ItemFn, TypeBareFn};
+        use proc_macro2::Ident;
+        use syn::{
+            spanned::Spanned, FnArg, ImplItem, ItemFn, ItemImpl, Pat,
+            Signature, TypeBareFn,
+        };
 
         use super::*;
 
@@ -1229,18 +2871,96 @@ mod rua_fn {
                         .target_type("RuaFn")
                         .build()
                 };
+                let mut f = (&value.sig).try_into().map_err(error_mapper)?;
+                apply_monomorphize_hints(&mut f, &value.attrs)
+                    .map_err(error_mapper)?;
+                Ok(f)
+            }
+        }
+
+        /// Converts the methods of the impl block, with `Self` resolved to
+        /// the type of the block. Associated consts and types are skipped,
+        /// and so are the methods that are generic without hints.
+        pub fn convert_impl_methods(
+            value: &ItemImpl,
+        ) -> Result<Vec<RuaSigFn>, ConversionError> {
+            let error_mapper = |err: ConversionError| {
+                err.builder_for_next()
+                    .span(&value.span())
+                    .source_type("syn::ItemImpl")
+                    .target_type("RuaSigFn")
+                    .build()
+            };
+            let self_ty: RuaType =
+                value.self_ty.as_ref().try_into().map_err(error_mapper)?;
+            let mut methods = Vec::new();
+            for item in value.items.iter() {
+                if let ImplItem::Fn(f) = item {
+                    let mut method: RuaSigFn =
+                        (&f.sig).try_into().map_err(error_mapper)?;
+                    apply_monomorphize_hints(&mut method, &f.attrs)
+                        .map_err(error_mapper)?;
+                    if skip_generic(&method) {
+                        continue;
+                    }
+                    method.resolve_self(&self_ty);
+                    methods.push(method);
+                }
+            }
+            Ok(methods)
+        }
+
+        impl TryFrom<&Signature> for RuaSigFn {
+            type Error = ConversionError;
+
+            fn try_from(value: &Signature) -> Result<Self, Self::Error> {
+                let error_mapper = |err: ConversionError| {
+                    err.builder_for_next()
+                        .span(&value.span())
+                        .source_type("syn::Signature")
+                        .target_type("RuaSigFn")
+                        .build()
+                };
+                let receiver = match value.receiver() {
+                    Some(receiver) => Some(Box::new(
+                        receiver
+                            .ty
+                            .as_ref()
+                            .try_into()
+                            .map_err(error_mapper)?,
+                    )),
+                    None => None,
+                };
                 let params = value
-                    .sig
                     .inputs
                     .iter()
-                    .map(|param| param.try_into().map_err(error_mapper))
-                    .collect::<Result<Vec<_>, _>>()?;
-                let ret =
-                    (&value.sig.output).try_into().map_err(error_mapper)?;
+                    .filter(|param| !matches!(param, FnArg::Receiver(_)))
+                    .enumerate()
+                    .map(|(i, param)| match param {
+                        // `_` and destructuring patterns have no name to keep
+                        FnArg::Typed(typed)
+                            if !matches!(typed.pat.as_ref(), Pat::Ident(_)) =>
+                        {
+                            let name = Ident::new(
+                                &format!("arg{}", i),
+                                typed.pat.span(),
+                            );
+                            Ok(RuaVar {
+                                name: (&name).try_into()?,
+                                ty: Box::new(typed.ty.as_ref().try_into()?),
+                                default: None,
+                            })
+                        }
+                        _ => param.try_into(),
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(error_mapper)?;
+                let ret = (&value.output).try_into().map_err(error_mapper)?;
                 Ok(RuaSigFn {
-                    name: (&value.sig.ident)
-                        .try_into()
+                    name: (&value.ident).try_into().map_err(error_mapper)?,
+                    generics: convert_generics(&value.generics)
                         .map_err(error_mapper)?,
+                    receiver,
                     params,
                     ret: Box::new(ret),
                 })
@@ -1273,6 +2993,227 @@ mod rua_fn {
     }
 }
 
+pub use rua_trait::*;
+
+mod rua_trait {
+    use super::*;
+
+    /// Represents a trait in Rust. Only the signatures of the methods are
+    /// kept, default method bodies are dropped. The methods that are generic
+    /// without hints are skipped.
+    #[rua_model_derive]
+    pub struct RuaTrait {
+        /// Represents the name of the trait.
+        pub name: RuaName,
+        /// Represents the generic parameters of the trait, in declaration
+        /// order.
+        pub generics: Vec<RuaGenericParam>,
+        /// Represents the methods of the trait.
+        pub methods: Vec<RuaSigFn>,
+        /// Represents the names of the associated types of the trait.
+        pub assoc_types: Vec<RuaName>,
+    }
+
+    impl RuaNamed for RuaTrait {
+        fn name(&self) -> &RuaName {
+            &self.name
+        }
+    }
+
+    pub use syn_convert::*;
+
+    mod syn_convert {
+        use syn::{spanned::Spanned, ItemTrait, TraitItem};
+
+        use super::*;
+
+        impl TryFrom<&ItemTrait> for RuaTrait {
+            type Error = ConversionError;
+
+            fn try_from(value: &ItemTrait) -> Result<Self, Self::Error> {
+                let error_mapper = |err: ConversionError| {
+                    err.builder_for_next()
+                        .span(&value.span())
+                        .source_type("syn::ItemTrait")
+                        .target_type("RuaTrait")
+                        .build()
+                };
+                let mut methods = Vec::new();
+                let mut assoc_types = Vec::new();
+                for item in value.items.iter() {
+                    match item {
+                        TraitItem::Fn(f) => {
+                            let mut method =
+                                (&f.sig).try_into().map_err(error_mapper)?;
+                            apply_monomorphize_hints(&mut method, &f.attrs)
+                                .map_err(error_mapper)?;
+                            if !skip_generic(&method) {
+                                methods.push(method);
+                            }
+                        }
+                        TraitItem::Type(ty) => assoc_types.push(
+                            (&ty.ident).try_into().map_err(error_mapper)?,
+                        ),
+                        // associated consts and macros are not supported yet
+                        _ => {}
+                    }
+                }
+                Ok(RuaTrait {
+                    name: (&value.ident).try_into().map_err(error_mapper)?,
+                    generics: convert_generics(&value.generics)
+                        .map_err(error_mapper)?,
+                    methods,
+                    assoc_types,
+                })
+            }
+        }
+    }
+}
+
+pub use rua_impl::*;
+
+mod rua_impl {
+    use super::*;
+
+    /// Represents an inherent impl block in Rust, i.e. `impl Foo { ... }`.
+    /// Only the signatures of the methods are kept, with `Self` resolved to
+    /// the type of the block.
+    #[rua_model_derive]
+    pub struct RuaImpl {
+        /// Represents the name of the type the block implements, e.g. `Foo`
+        /// for `impl<T> Foo<T>`.
+        pub name: RuaName,
+        /// Represents the type the block implements, i.e. `Self`.
+        pub self_ty: RuaType,
+        /// Represents the methods of the block, see [convert_impl_methods].
+        pub methods: Vec<RuaSigFn>,
+    }
+
+    impl RuaNamed for RuaImpl {
+        fn name(&self) -> &RuaName {
+            &self.name
+        }
+    }
+
+    mod syn_convert {
+        use syn::{spanned::Spanned, ItemImpl};
+
+        use super::*;
+
+        impl TryFrom<&ItemImpl> for RuaImpl {
+            type Error = ConversionError;
+
+            fn try_from(value: &ItemImpl) -> Result<Self, Self::Error> {
+                let error_mapper = |err: ConversionError| {
+                    err.builder_for_next()
+                        .span(&value.span())
+                        .source_type("syn::ItemImpl")
+                        .target_type("RuaImpl")
+                        .build()
+                };
+                let self_ty: RuaType =
+                    value.self_ty.as_ref().try_into().map_err(error_mapper)?;
+                let name = match &self_ty {
+                    RuaType::Custom(custom) => custom.name.clone(),
+                    ty => {
+                        return Err(ConversionError::builder()
+                            .span(&value.self_ty.span())
+                            .source_type("syn::ItemImpl")
+                            .target_type("RuaImpl")
+                            .message(format!("unsupported impl for {}", ty))
+                            .build())
+                    }
+                };
+                Ok(RuaImpl {
+                    name,
+                    self_ty,
+                    methods: convert_impl_methods(value)
+                        .map_err(error_mapper)?,
+                })
+            }
+        }
+    }
+}
+
+pub use rua_item::*;
+
+mod rua_item {
+    use super::*;
+
+    /// Represents an item in Rust that can be converted to a model.
+    #[rua_model_derive]
+    pub enum RuaItem {
+        /// Represents a struct.
+        Struct(RuaStruct),
+        /// Represents an enum.
+        Enum(RuaEnum),
+        /// Represents a function.
+        Fn(RuaSigFn),
+        /// Represents a trait.
+        Trait(RuaTrait),
+        /// Represents an inherent impl block.
+        Impl(RuaImpl),
+    }
+
+    impl RuaNamed for RuaItem {
+        fn name(&self) -> &RuaName {
+            match self {
+                RuaItem::Struct(s) => s.name(),
+                RuaItem::Enum(e) => e.name(),
+                RuaItem::Fn(f) => f.name(),
+                RuaItem::Trait(t) => t.name(),
+                RuaItem::Impl(i) => i.name(),
+            }
+        }
+    }
+
+    pub use syn_convert::*;
+
+    mod syn_convert {
+        use syn::{spanned::Spanned, Item};
+
+        use super::*;
+
+        /// Converts the item with the corresponding `TryFrom` impl. Returns
+        /// `None` for the items that have no model, e.g. `use` declarations
+        /// and trait impls, whose methods are those of the trait.
+        pub fn convert_item(
+            item: &Item,
+        ) -> Result<Option<RuaItem>, ConversionError> {
+            let error_mapper = |err: ConversionError| {
+                err.builder_for_next()
+                    .span(&item.span())
+                    .source_type("syn::Item")
+                    .target_type("RuaItem")
+                    .build()
+            };
+            let item = match item {
+                Item::Struct(s) => {
+                    RuaItem::Struct(s.try_into().map_err(error_mapper)?)
+                }
+                Item::Enum(e) => {
+                    RuaItem::Enum(e.clone().try_into().map_err(error_mapper)?)
+                }
+                Item::Fn(f) => {
+                    let f: RuaSigFn = f.try_into().map_err(error_mapper)?;
+                    if skip_generic(&f) {
+                        return Ok(None);
+                    }
+                    RuaItem::Fn(f)
+                }
+                Item::Trait(t) => {
+                    RuaItem::Trait(t.try_into().map_err(error_mapper)?)
+                }
+                Item::Impl(i) if i.trait_.is_none() => {
+                    RuaItem::Impl(i.try_into().map_err(error_mapper)?)
+                }
+                _ => return Ok(None),
+            };
+            Ok(Some(item))
+        }
+    }
+}
+
 pub use rua_pointer::*;
 
 mod rua_pointer {
@@ -1284,6 +3225,9 @@ mod rua_pointer {
         /// Represents if the pointer is a constant pointer. If it is not constant,
         /// it is a mutable pointer.
         pub is_const: bool,
+        /// Represents if the pointer is known to be non-null, i.e.
+        /// [`std::ptr::NonNull`].
+        pub is_non_null: bool,
         /// Represents the type of the value the pointer points to.
         pub ty: Box<RuaType>,
     }
@@ -1314,6 +3258,7 @@ mod rua_pointer {
                 };
                 Ok(RuaPointer {
                     is_const: value.const_token.is_some(),
+                    is_non_null: false,
                     ty: Box::new(
                         (value.elem.as_ref())
                             .try_into()
@@ -1325,6 +3270,49 @@ mod rua_pointer {
     }
 }
 
+pub use rua_non_zero::*;
+
+mod rua_non_zero {
+    use super::*;
+
+    /// Represents a non-zero integer in Rust, i.e. `NonZeroU32` or
+    /// `NonZero<u32>`.
+    #[rua_model_derive]
+    pub struct RuaNonZero {
+        /// Represents the underlying integer type.
+        pub ty: Box<RuaType>,
+    }
+
+    impl From<RuaNonZero> for RuaType {
+        fn from(value: RuaNonZero) -> Self {
+            RuaType::NonZero(value)
+        }
+    }
+
+    impl RuaNonZero {
+        /// Returns the non-zero integer named `NonZero{suffix}`, e.g.
+        /// `NonZeroU64` for the suffix `U64`.
+        pub(crate) fn from_suffix(suffix: &str) -> Option<Self> {
+            let ty = match suffix {
+                "U8" => RuaType::U8,
+                "U16" => RuaType::U16,
+                "U32" => RuaType::U32,
+                "U64" => RuaType::U64,
+                "U128" => RuaType::U128,
+                "Usize" => RuaType::Usize,
+                "I8" => RuaType::I8,
+                "I16" => RuaType::I16,
+                "I32" => RuaType::I32,
+                "I64" => RuaType::I64,
+                "I128" => RuaType::I128,
+                "Isize" => RuaType::Isize,
+                _ => return None,
+            };
+            Some(Self { ty: Box::new(ty) })
+        }
+    }
+}
+
 pub use rua_ref::*;
 
 mod rua_ref {
@@ -1336,10 +3324,24 @@ mod rua_ref {
         /// Represents if the reference is a mutable reference. If it is not
         /// mutable, it is an immutable reference.
         pub is_mut: bool,
+        /// Represents the name of the lifetime of the reference without the
+        /// leading `'`, e.g. `a` for `&'a T`, if it is explicit.
+        pub lifetime: Option<String>,
         /// Represents the type of the value the reference points to.
         pub ty: Box<RuaType>,
     }
 
+    impl RuaReference {
+        /// Creates a reference to the type without an explicit lifetime.
+        pub fn new(is_mut: bool, ty: RuaType) -> Self {
+            Self {
+                is_mut,
+                lifetime: None,
+                ty: Box::new(ty),
+            }
+        }
+    }
+
     impl From<RuaReference> for RuaType {
         fn from(value: RuaReference) -> Self {
             RuaType::Reference(value)
@@ -1366,6 +3368,10 @@ mod rua_ref {
                 };
                 Ok(RuaReference {
                     is_mut: value.mutability.is_some(),
+                    lifetime: value
+                        .lifetime
+                        .as_ref()
+                        .map(|lifetime| lifetime.ident.to_string()),
                     ty: Box::new(
                         (value.elem.as_ref())
                             .try_into()
@@ -1376,3 +3382,1093 @@ mod rua_ref {
         }
     }
 }
+
+pub use rua_custom::*;
+
+mod rua_custom {
+    use super::*;
+
+    /// Represents a type that is not built into the models, e.g. a user type
+    /// or `Vec<u8>`, by its path and its generic type arguments.
+    #[rua_model_derive]
+    pub struct RuaCustom {
+        /// Represents the segments of the path before the name, e.g. `std`
+        /// and `time` for `std::time::Duration`, or nothing if the name is
+        /// not qualified.
+        pub path: Vec<String>,
+        /// Represents the name of the type, i.e. the last segment of the path.
+        pub name: RuaName,
+        /// Represents the generic type arguments, e.g. `u8` for `Vec<u8>`.
+        /// Lifetimes and const arguments are not kept.
+        pub args: Vec<RuaType>,
+    }
+
+    impl RuaCustom {
+        /// Creates an unqualified custom type without generic arguments.
+        pub fn new(name: RuaName) -> Self {
+            Self {
+                path: Vec::new(),
+                name,
+                args: Vec::new(),
+            }
+        }
+
+        /// Returns the path including the name, e.g. `std::time::Duration`.
+        pub fn qualified_name(&self) -> String {
+            self.path
+                .iter()
+                .map(String::as_str)
+                .chain(std::iter::once(self.name.render()))
+                .collect::<Vec<_>>()
+                .join("::")
+        }
+
+        /// Checks if the type is the unqualified name `alias` without generic
+        /// arguments, e.g. `Char` but not `Char<T>` nor `my::Char`.
+        pub(crate) fn is_alias_of(&self, alias: &str) -> bool {
+            self.path.is_empty()
+                && self.args.is_empty()
+                && self.name.render() == alias
+        }
+    }
+
+    impl From<RuaName> for RuaCustom {
+        fn from(value: RuaName) -> Self {
+            Self::new(value)
+        }
+    }
+
+    impl From<RuaCustom> for RuaType {
+        fn from(value: RuaCustom) -> Self {
+            RuaType::Custom(value)
+        }
+    }
+}
+
+pub use rua_model::*;
+
+mod rua_model {
+    use super::*;
+    use crate::errors::RuaError;
+
+    /// Represents the items collected from one or more crates, so that the
+    /// types they refer to can be looked up, including across crates.
+    #[rua_model_derive]
+    #[derive(Default)]
+    pub struct RuaModel {
+        /// Represents the items along with the path of the module declaring
+        /// them, starting with the name of the crate, e.g. `["shapes",
+        /// "circle"]` for the items of `shapes::circle`.
+        pub items: Vec<(Vec<String>, RuaItem)>,
+    }
+
+    impl RuaModel {
+        /// Creates an empty model.
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Creates the model of the crate with the name from the items along
+        /// with the path of their module relative to the crate root, e.g.
+        /// `["circle"]` for `crate::circle`.
+        pub fn from_crate(
+            name: impl AsRef<str>,
+            items: impl IntoIterator<Item = (Vec<String>, RuaItem)>,
+        ) -> Self {
+            let name = name.as_ref().to_owned();
+            let items = items
+                .into_iter()
+                .map(|(module, item)| {
+                    ([vec![name.clone()], module].concat(), item)
+                })
+                .collect();
+            Self { items }
+        }
+
+        /// Merges the items of both models, e.g. of two crates of the same
+        /// SDK, so that their references to each other can be resolved.
+        /// Fails if both declare a struct, an enum, a function or a trait
+        /// with the same path, e.g. when merging a crate with itself. Impl
+        /// blocks do not collide.
+        pub fn merge(mut self, other: RuaModel) -> Result<RuaModel, RuaError> {
+            for (module, item) in other.items.iter() {
+                if matches!(item, RuaItem::Impl(_)) {
+                    continue;
+                }
+                let collides = self.items.iter().any(|(m, i)| {
+                    m == module
+                        && !matches!(i, RuaItem::Impl(_))
+                        && i.name().render() == item.name().render()
+                });
+                if collides {
+                    return Err(RuaError::ConversionError(
+                        ConversionError::builder()
+                            .source_type("RuaModel")
+                            .target_type("RuaModel")
+                            .message(format!(
+                                "{}::{} is declared in both models",
+                                module.join("::"),
+                                item.name().render()
+                            ))
+                            .build(),
+                    ));
+                }
+            }
+            self.items.extend(other.items);
+            Ok(self)
+        }
+
+        /// Returns the path of the module declaring the struct or enum the
+        /// custom type refers to from the module, along with the item, if
+        /// any. `crate`, `self` and `super` are followed, and other paths are
+        /// looked up relative to the module, then from the crate names, e.g.
+        /// `users::User` for the `User` of the `users` crate. Since `use`
+        /// declarations are not modeled, an unqualified name that is not
+        /// declared in the module resolves to the only type with that name.
+        pub fn resolve(
+            &self,
+            module: &[String],
+            custom: &RuaCustom,
+        ) -> Option<(&[String], &RuaItem)> {
+            let name = custom.name.render();
+            let declared = self
+                .items
+                .iter()
+                .filter(|(_, item)| {
+                    matches!(item, RuaItem::Struct(_) | RuaItem::Enum(_))
+                        && item.name().render() == name
+                })
+                .map(|(module, item)| (module.as_slice(), item))
+                .collect::<Vec<_>>();
+            let modules = declared
+                .iter()
+                .map(|(module, _)| *module)
+                .collect::<Vec<_>>();
+            // the first segment of the modules is the crate name
+            let found = resolve_module(&modules, 1, module, &custom.path)?;
+            declared.into_iter().find(|(module, _)| *module == found)
+        }
+
+        /// Returns an error for each custom type a field of the structs and
+        /// enums refers to that [RuaModel::resolve] cannot find, e.g. `Tag`
+        /// in `struct Post { tags: Vec<Tag> }` when no crate of the model
+        /// declares it. The types of `std`, `core` and `alloc`, those of the
+        /// prelude, e.g. `Vec`, and the generic parameters are not reported.
+        pub fn unresolved(&self) -> Vec<ConversionError> {
+            let mut errors = Vec::new();
+            for (module, item) in self.items.iter() {
+                let (generics, structs) = match item {
+                    RuaItem::Struct(s) => {
+                        let generics = match s {
+                            RuaStruct::Named(named) => &named.generics,
+                            RuaStruct::Tuple(tuple) => &tuple.generics,
+                            RuaStruct::Unit(unit) => &unit.generics,
+                        };
+                        (generics, vec![s])
+                    }
+                    RuaItem::Enum(e) => {
+                        (&e.generics, e.variants.iter().collect())
+                    }
+                    _ => continue,
+                };
+                let mut customs = Vec::new();
+                for s in structs {
+                    match s {
+                        RuaStruct::Named(named) => {
+                            for field in named.fields.iter() {
+                                field.ty.collect_customs(&mut customs);
+                            }
+                        }
+                        RuaStruct::Tuple(tuple) => {
+                            for ty in tuple.tys.iter() {
+                                ty.collect_customs(&mut customs);
+                            }
+                        }
+                        RuaStruct::Unit(_) => {}
+                    }
+                }
+                for custom in customs {
+                    let is_generic = custom.path.is_empty()
+                        && generics
+                            .iter()
+                            .any(|param| param.name() == custom.name.render());
+                    if is_generic
+                        || is_external(custom)
+                        || self.resolve(module, custom).is_some()
+                    {
+                        continue;
+                    }
+                    errors.push(
+                        ConversionError::builder()
+                            .source_type("RuaType")
+                            .target_type("RuaModel")
+                            .message(format!(
+                                "{}::{} refers to the unresolved type {}",
+                                module.join("::"),
+                                item.name().render(),
+                                custom.qualified_name()
+                            ))
+                            .build(),
+                    );
+                }
+            }
+            errors
+        }
+    }
+
+    /// The types of the prelude that may be used unqualified without being
+    /// declared in any crate.
+    const PRELUDE: [&str; 4] = ["Box", "Option", "Result", "Vec"];
+
+    /// Checks if the type is declared outside of the crates of a model, i.e.
+    /// qualified with `std`, `core` or `alloc`, or one of [PRELUDE].
+    fn is_external(custom: &RuaCustom) -> bool {
+        match custom.path.first() {
+            Some(krate) => ["std", "core", "alloc"].contains(&krate.as_str()),
+            None => PRELUDE.contains(&custom.name.render()),
+        }
+    }
+
+    /// Returns the module declaring the type at the path, written in the
+    /// module, among the `declared` modules, which declare a type with its
+    /// name. The first `root` segments of the modules are the crate root.
+    /// `crate`, `self` and `super` are followed, and other paths are looked
+    /// up relative to the module, then as absolute paths. Since `use`
+    /// declarations are not modeled, an unqualified name that is not
+    /// declared in the module resolves to the only type with that name.
+    pub(crate) fn resolve_module<'a>(
+        declared: &[&'a [String]],
+        root: usize,
+        module: &[String],
+        path: &[String],
+    ) -> Option<&'a [String]> {
+        let find = |path: &[String]| {
+            declared.iter().copied().find(|module| *module == path)
+        };
+        let mut base = module.to_vec();
+        let mut rest = path;
+        match rest.first().map(String::as_str) {
+            Some("crate") => {
+                base.truncate(root);
+                rest = &rest[1..];
+            }
+            Some("self") => rest = &rest[1..],
+            Some("super") => {
+                while rest.first().is_some_and(|segment| segment == "super") {
+                    if base.len() <= root {
+                        return None;
+                    }
+                    base.pop();
+                    rest = &rest[1..];
+                }
+            }
+            _ => {}
+        }
+        if let Some(found) = find(&[base, rest.to_vec()].concat()) {
+            return Some(found);
+        }
+        if path.is_empty() {
+            return match declared {
+                [only] => Some(*only),
+                _ => None,
+            };
+        }
+        find(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{
+        assert_converts, assert_fails, assert_round_trips, convert_file,
+        convert_type,
+    };
+
+    fn custom(name: &str, args: Vec<RuaType>) -> RuaType {
+        RuaType::Custom(RuaCustom {
+            path: Vec::new(),
+            name: RuaName::new(name, RuaCase::PascalCase),
+            args,
+        })
+    }
+
+    #[test]
+    fn generic_arguments_nest_in_arrays_and_tuples() {
+        let tuple = RuaType::Tuple(RuaTuple {
+            tys: vec![
+                custom("Vec", vec![RuaType::U8]),
+                custom("Option", vec![RuaType::I32]),
+            ],
+        });
+        assert_converts(
+            "[(Vec<u8>, Option<i32>); 3]",
+            &RuaType::Array(RuaArray::new(tuple.clone(), RuaArrayLen::Num(3))),
+        );
+        assert_converts(
+            "&[(Vec<u8>, Option<i32>)]",
+            &RuaType::Reference(RuaReference::new(
+                false,
+                RuaType::Slice(RuaSlice::new(tuple)),
+            )),
+        );
+        assert_round_trips("[(Vec<u8>, Option<i32>); 3]");
+    }
+
+    #[test]
+    fn custom_types_keep_their_path() {
+        let ty = convert_type("std::vec::Vec<u8>").unwrap();
+        let custom = match ty {
+            RuaType::Custom(custom) => custom,
+            ty => panic!("expected a custom type, got {:?}", ty),
+        };
+        assert_eq!(custom.path, ["std", "vec"]);
+        assert_eq!(custom.qualified_name(), "std::vec::Vec");
+        assert_eq!(custom.args, [RuaType::U8]);
+    }
+
+    #[test]
+    fn builtin_types_are_only_recognized_from_std() {
+        assert_converts("core::primitive::u8", &RuaType::U8);
+        assert_converts("std::primitive::str", &RuaType::Str);
+        assert_converts("std::string::String", &RuaType::String);
+        assert_converts("alloc::string::String", &RuaType::String);
+        for (src, path, name) in [
+            ("my::u8", vec!["my"], "u8"),
+            ("my::String", vec!["my"], "String"),
+            ("std::ffi::CString", vec!["std", "ffi"], "CString"),
+        ] {
+            let custom = match convert_type(src).unwrap() {
+                RuaType::Custom(custom) => custom,
+                ty => panic!("expected a custom type, got {:?}", ty),
+            };
+            assert_eq!(custom.path, path, "{}", src);
+            assert_eq!(custom.name.render(), name, "{}", src);
+        }
+    }
+
+    #[test]
+    fn cow_converts_to_its_inner_type() {
+        assert_converts("Cow<'a, str>", &RuaType::Str);
+        assert_converts(
+            "Cow<'_, [u8]>",
+            &RuaType::Slice(RuaSlice::new(RuaType::U8)),
+        );
+        assert_converts("std::borrow::Cow<'static, str>", &RuaType::Str);
+        assert_converts(
+            "my::Cow<str>",
+            &RuaType::Custom(RuaCustom {
+                path: vec!["my".to_owned()],
+                name: RuaName::new("Cow", RuaCase::PascalCase),
+                args: vec![RuaType::Str],
+            }),
+        );
+    }
+
+    #[test]
+    fn non_null_and_non_zero_convert() {
+        let non_null = RuaType::Pointer(RuaPointer {
+            is_const: false,
+            is_non_null: true,
+            ty: Box::new(RuaType::U8),
+        });
+        assert_converts("NonNull<u8>", &non_null);
+        assert_converts("core::ptr::NonNull<u8>", &non_null);
+        let non_zero = RuaType::NonZero(RuaNonZero {
+            ty: Box::new(RuaType::U64),
+        });
+        assert_converts("NonZeroU64", &non_zero);
+        assert_converts("std::num::NonZeroU64", &non_zero);
+        assert_converts("NonZero<u64>", &non_zero);
+        assert!(matches!(
+            convert_type("my::NonZeroU64"),
+            Ok(RuaType::Custom(_))
+        ));
+        let err = assert_fails("NonZero<f32>");
+        assert!(err.to_string().contains("not an integer"));
+    }
+
+    fn convert_struct(src: &str) -> RuaStruct {
+        let item = syn::parse_str::<syn::ItemStruct>(src)
+            .unwrap_or_else(|err| panic!("invalid struct {:?}: {}", src, err));
+        (&item).try_into().unwrap_or_else(|err| {
+            panic!("failed to convert {:?}: {}", src, err)
+        })
+    }
+
+    fn named_struct(src: &str) -> RuaNamedStruct {
+        match convert_struct(src) {
+            RuaStruct::Named(named) => named,
+            s => panic!("expected a named struct, got {:?}", s),
+        }
+    }
+
+    #[test]
+    fn phantom_data_is_dropped_from_named_structs() {
+        let named = named_struct(
+            "struct Id<T> { id: u64, marker: std::marker::PhantomData<T> }",
+        );
+        let names = named
+            .fields
+            .iter()
+            .map(|field| field.name.render())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["id"]);
+    }
+
+    #[test]
+    fn phantom_data_keeps_tuple_struct_indices() {
+        let s = convert_struct("struct Id<T>(PhantomData<T>, u64);");
+        let tuple = match s {
+            RuaStruct::Tuple(tuple) => tuple,
+            s => panic!("expected a tuple struct, got {:?}", s),
+        };
+        assert_eq!(tuple.tys.len(), 2);
+        assert_eq!(tuple.tys[1], RuaType::U64);
+    }
+
+    #[test]
+    fn tricky_names_convert_to_valid_snake_case() {
+        for (name, expected) in [
+            ("HTTPServer", "http_server"),
+            ("XMLHttpRequest", "xml_http_request"),
+            ("FOO_BAR", "foo_bar"),
+        ] {
+            let name = RuaName::new(name, RuaCase::PascalCase);
+            let snake = name.try_convert(RuaCase::SnakeCase).unwrap();
+            assert_eq!(snake.render(), expected);
+            assert!(snake.check());
+        }
+    }
+
+    #[test]
+    fn lossy_idents_fall_back_to_the_best_guess_case() {
+        for (ident, name, case) in [
+            ("XMLHttpRequest", "XMLHttpRequest", RuaCase::PascalCase),
+            ("_internal", "_internal", RuaCase::SnakeCase),
+            ("_MAX", "_max", RuaCase::SnakeCase),
+            ("_fooBar", "_foo_bar", RuaCase::SnakeCase),
+            ("__Private", "__Private", RuaCase::PascalCase),
+        ] {
+            let ident =
+                proc_macro2::Ident::new(ident, proc_macro2::Span::call_site());
+            let lossy = RuaName::from_ident_lossy(&ident);
+            assert_eq!(lossy.render(), name);
+            assert_eq!(lossy.case(), &case);
+        }
+    }
+
+    #[test]
+    fn case_predicates_match_the_original_rules() {
+        assert!(!RuaCase::SnakeCase.check("foo1"));
+        assert!(RuaCase::SnakeCase.check("foo"));
+        assert!(!RuaCase::CamelCase.check("foo"));
+        assert!(RuaCase::PascalCase.check("FOO_BAR"));
+        assert!(RuaCase::CamelCase.check("foo_Bar"));
+    }
+
+    #[test]
+    fn add_items_skips_disabled_modules() {
+        let file = syn::parse_file(
+            r#"mod common {}
+               #[cfg(feature = "ios")] mod ios { mod ffi {} }"#,
+        )
+        .unwrap();
+        let tree = |cfg: Option<&RuaCfg>| {
+            let mut tree = RuaModTree::new(RuaMod::new(
+                "app",
+                RuaModType::CrateModule,
+                None,
+                true,
+            ));
+            tree.add_items(&file.items, cfg).unwrap();
+            tree
+        };
+        let mut cfg = RuaCfg::new();
+        assert!(tree(Some(&cfg)).find(&["ios"]).is_none());
+        cfg.enable_feature("ios");
+        assert!(tree(Some(&cfg)).find(&["ios", "ffi"]).is_some());
+        assert!(tree(None).find(&["ios"]).is_some());
+    }
+
+    #[test]
+    fn aliases_are_custom_types_by_default() {
+        let char_alias = custom("Char", Vec::new());
+        assert_converts("Char", &char_alias);
+        assert_converts("Unit", &custom("Unit", Vec::new()));
+        assert_eq!(char_alias.resolve_aliases(), RuaType::Char);
+        let nested = convert_type("(Vec<Char>, unit)").unwrap();
+        assert_eq!(
+            nested.resolve_aliases(),
+            convert_type("(Vec<char>, ())").unwrap()
+        );
+        // only the unqualified names are aliases
+        let qualified = convert_type("my::Char").unwrap();
+        assert_eq!(qualified.clone().resolve_aliases(), qualified);
+    }
+
+    #[test]
+    fn mod_tree_mirrors_nested_modules_and_their_files() {
+        let file = syn::parse_file(
+            r#"pub mod a { pub mod b { mod c { mod d; } } }
+               mod util;
+               #[path = "gen/bindings.rs"] mod ffi;
+               #[cfg(test)] mod tests {}"#,
+        )
+        .unwrap();
+        let mut tree = RuaModTree::new(RuaMod::new(
+            "app",
+            RuaModType::CrateModule,
+            Some(PathBuf::from("src/lib.rs")),
+            true,
+        ));
+        tree.add_items(&file.items, Some(&RuaCfg::new())).unwrap();
+        assert_eq!(tree.depth(), 5);
+        let names = tree
+            .children
+            .iter()
+            .map(|child| child.module.name().render())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["a", "util", "ffi"]);
+        let module = |path: &[&str]| &tree.find(path).unwrap().module;
+        let c = module(&["a", "b", "c"]);
+        assert_eq!(c.ty(), &RuaModType::InlineModule);
+        assert!(!c.is_public());
+        assert_eq!(c.root_path(), &Some(PathBuf::from("src/lib.rs")));
+        let d = module(&["a", "b", "c", "d"]);
+        assert_eq!(d.ty(), &RuaModType::FileModule);
+        assert_eq!(d.root_path(), &Some(PathBuf::from("src/a/b/c/d.rs")));
+        let ffi = module(&["ffi"]);
+        assert_eq!(
+            ffi.root_path(),
+            &Some(PathBuf::from("src/gen/bindings.rs"))
+        );
+        let util = module(&["util"]);
+        assert_eq!(util.root_path(), &Some(PathBuf::from("src/util.rs")));
+    }
+
+    #[test]
+    fn discriminants_on_data_variants_are_rejected() {
+        let item =
+            syn::parse_str::<syn::ItemEnum>("enum E { A(u8) = 1 }").unwrap();
+        let err = RuaEnum::try_from(item).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("variant A has fields and a discriminant"),
+            "{}",
+            err
+        );
+        let item =
+            syn::parse_str::<syn::ItemEnum>("enum E { A = 1, B }").unwrap();
+        assert!(RuaEnum::try_from(item).is_ok());
+    }
+
+    #[test]
+    fn raw_identifiers_lose_their_prefix() {
+        let named = named_struct("struct S { r#type: u8, r#async: bool }");
+        assert_eq!(named.fields[0].name.render(), "type");
+        assert!(named.field("async").is_some());
+    }
+
+    #[test]
+    fn contains_finds_nested_floats() {
+        let named =
+            named_struct("struct S { mixed: (f64, u8), ints: (u8, u8) }");
+        let is_float = |ty: &RuaType| matches!(ty, RuaType::F32 | RuaType::F64);
+        assert!(named.field("mixed").unwrap().ty.contains(is_float));
+        assert!(!named.field("ints").unwrap().ty.contains(is_float));
+    }
+
+    #[test]
+    fn field_looks_up_by_snake_case_name() {
+        let named = named_struct("struct S { x_pos: i32, yPos: i32 }");
+        assert_eq!(named.field("x_pos").unwrap().ty.as_ref(), &RuaType::I32);
+        assert!(named.field("y_pos").is_some());
+        assert!(named.field("z_pos").is_none());
+    }
+
+    #[test]
+    fn non_ascii_names_convert() {
+        let s = convert_struct("struct Café { prix: u32 }");
+        assert_eq!(s.name().render(), "Café");
+        let snake = s.name().try_convert(RuaCase::SnakeCase).unwrap();
+        assert_eq!(snake.render(), "café");
+        // a single word is snake_case too, so this falls back to it
+        assert_eq!(s.name().convert(RuaCase::CamelCase).render(), "café");
+        assert!(RuaCase::SnakeCase.check("straße"));
+        assert!(RuaCase::PascalCase.check("Éclair"));
+    }
+
+    #[test]
+    fn invalid_conversions_do_not_panic() {
+        let name = RuaName::new("_", RuaCase::SnakeCase);
+        assert_eq!(name.convert(RuaCase::PascalCase).render(), "_");
+        assert!(name.try_convert(RuaCase::PascalCase).is_err());
+    }
+
+    #[test]
+    fn trait_keeps_generics_signatures_and_associated_types() {
+        let item = syn::parse_str::<syn::ItemTrait>(
+            "trait Store<K> {
+                type Value;
+                fn get(&self, key: K) -> Option<u32>;
+                fn clear(&mut self) { }
+            }",
+        )
+        .unwrap();
+        let t = RuaTrait::try_from(&item).unwrap();
+        assert_eq!(t.name.render(), "Store");
+        assert_eq!(
+            t.generics,
+            [RuaGenericParam::Type {
+                name: "K".to_owned()
+            }]
+        );
+        let methods = t
+            .methods
+            .iter()
+            .map(|method| method.name.render())
+            .collect::<Vec<_>>();
+        assert_eq!(methods, ["get", "clear"]);
+        assert_eq!(t.assoc_types, [RuaName::new("Value", RuaCase::PascalCase)]);
+    }
+
+    #[test]
+    fn signature_errors_target_rua_sig_fn() {
+        let sig =
+            syn::parse_str::<syn::Signature>("fn f(x: <T as A>::B)").unwrap();
+        let err = RuaSigFn::try_from(&sig).unwrap_err();
+        assert!(err.to_string().contains("to RuaSigFn"));
+    }
+
+    #[test]
+    fn size_and_align_of_primitives_and_arrays() {
+        let layout = |ty: RuaType| ty.size_and_align().unwrap();
+        assert_eq!(layout(RuaType::U32), Some((4, 4)));
+        assert_eq!(layout(RuaType::U64), Some((8, 8)));
+        assert_eq!(layout(RuaType::Bool), Some((1, 1)));
+        assert_eq!(
+            layout(RuaType::Custom(RuaCustom::new(RuaName::new(
+                "Point",
+                RuaCase::PascalCase
+            )))),
+            None
+        );
+        assert_eq!(
+            layout(RuaType::Array(RuaArray::new(
+                RuaType::U16,
+                RuaArrayLen::Num(3)
+            ))),
+            Some((6, 2))
+        );
+        let huge = RuaType::Array(RuaArray::new(
+            RuaType::U64,
+            RuaArrayLen::Num(usize::MAX),
+        ));
+        assert!(huge.size_and_align().is_err());
+    }
+
+    #[test]
+    fn parse_converts_type_strings() {
+        assert_eq!(
+            RuaType::parse("Vec<u8>").unwrap(),
+            custom("Vec", vec![RuaType::U8])
+        );
+        assert_eq!(
+            RuaType::parse("&mut [i32]").unwrap(),
+            RuaType::Reference(RuaReference::new(
+                true,
+                RuaType::Slice(RuaSlice::new(RuaType::I32)),
+            ))
+        );
+        assert_eq!(
+            RuaType::parse("(u8, String)").unwrap(),
+            RuaType::Tuple(RuaTuple {
+                tys: vec![RuaType::U8, RuaType::String],
+            })
+        );
+    }
+
+    #[test]
+    fn as_overrides_the_field_type_and_points_errors_at_the_literal() {
+        let named = named_struct(
+            "struct S { #[rua(as = \"u64\")] id: <T as Id>::Raw }",
+        );
+        assert_eq!(*named.fields[0].ty, RuaType::U64);
+
+        let contents = "struct S {\n    #[rua(as = \"<T as Id>::Raw\")]\n    \
+                        id: u64,\n}\n";
+        let item = syn::parse_str::<syn::ItemStruct>(contents).unwrap();
+        let mut err = RuaStruct::try_from(&item).unwrap_err();
+        err.locate_in(contents);
+        let mut innermost: &dyn std::error::Error = &err;
+        while let Some(source) = innermost.source() {
+            innermost = source;
+        }
+        let innermost = innermost.downcast_ref::<ConversionError>().unwrap();
+        let (start, end) = innermost.byte_range().unwrap();
+        assert_eq!(&contents[start..end], "\"<T as Id>::Raw\"");
+    }
+
+    #[test]
+    fn generic_methods_are_monomorphized_or_skipped() {
+        let item = syn::parse_str::<syn::ItemTrait>(
+            "trait Store {
+                #[rua(monomorphize(T = \"u32\"))]
+                fn get<T>(&self) -> T;
+                fn put<T>(&mut self, value: T);
+            }",
+        )
+        .unwrap();
+        let t = RuaTrait::try_from(&item).unwrap();
+        assert_eq!(t.methods.len(), 1);
+        assert_eq!(t.methods[0].name.render(), "get");
+        assert!(t.methods[0].generics.is_empty());
+        assert_eq!(*t.methods[0].ret, RuaType::U32);
+
+        let item = syn::parse_str::<syn::ItemImpl>(
+            "impl Store {
+                #[rua(monomorphize(T = \"u32\"))]
+                fn get<T>(&self) -> T { todo!() }
+                fn put<T>(&mut self, value: T) {}
+            }",
+        )
+        .unwrap();
+        let methods = convert_impl_methods(&item).unwrap();
+        assert_eq!(methods.len(), 1);
+        assert_eq!(*methods[0].ret, RuaType::U32);
+    }
+
+    #[test]
+    fn impl_blocks_resolve_self() {
+        let item = syn::parse_str::<syn::Item>(
+            "impl Foo {
+                fn new() -> Self { Foo }
+                fn merge(&self, other: &Self) -> Self { Foo }
+            }",
+        )
+        .unwrap();
+        let i = match convert_item(&item).unwrap() {
+            Some(RuaItem::Impl(i)) => i,
+            item => panic!("expected an impl, got {:?}", item),
+        };
+        let foo = custom("Foo", Vec::new());
+        assert_eq!(i.name.render(), "Foo");
+        assert_eq!(i.self_ty, foo);
+        assert_eq!(*i.methods[0].ret, foo);
+        assert_eq!(
+            *i.methods[1].params[0].ty,
+            RuaType::Reference(RuaReference::new(false, foo.clone()))
+        );
+
+        let item =
+            syn::parse_str::<syn::Item>("impl Clone for Foo { }").unwrap();
+        assert_eq!(convert_item(&item).unwrap(), None);
+    }
+
+    /// Converts the items of the source as the model of the crate, declared
+    /// in its root module.
+    fn crate_model(name: &str, src: &str) -> RuaModel {
+        let items = convert_file(src, None)
+            .into_iter()
+            .map(|item| (Vec::new(), item));
+        RuaModel::from_crate(name, items)
+    }
+
+    #[test]
+    fn merged_models_resolve_cross_crate_references() {
+        let users = crate_model("users", "pub struct User { id: u64 }");
+        let orders = crate_model(
+            "orders",
+            "pub struct Order { user: users::User, item: Item } \
+             pub struct Item { sku: u32 }",
+        );
+        let model = orders.merge(users.clone()).unwrap();
+        let order = match &model.items[0].1 {
+            RuaItem::Struct(RuaStruct::Named(named)) => named,
+            _ => panic!("expected a named struct"),
+        };
+        let module = ["orders".to_owned()];
+        for (field, krate) in [("user", "users"), ("item", "orders")] {
+            let ty = &order.field(field).unwrap().ty;
+            let custom = match ty.as_ref() {
+                RuaType::Custom(custom) => custom,
+                _ => panic!("expected a custom type"),
+            };
+            let (found, _) = model.resolve(&module, custom).unwrap();
+            assert_eq!(found, [krate.to_owned()]);
+        }
+        let err = model.merge(users).unwrap_err();
+        assert!(err.to_string().contains("users::User"), "{}", err);
+    }
+
+    #[test]
+    fn unresolved_custom_types_are_reported() {
+        let model = crate_model(
+            "blog",
+            "pub struct Post<T> { author: User, tags: Vec<Tag>, \
+             extra: T, at: std::time::SystemTime } \
+             pub struct User { id: u64 } \
+             pub enum Event { Published(Post<u8>), Tagged { tag: Tag } }",
+        );
+        let errors = model.unresolved();
+        assert_eq!(errors.len(), 2, "{:?}", errors);
+        assert!(
+            errors[0].to_string().contains("blog::Post"),
+            "{}",
+            errors[0]
+        );
+        for err in errors.iter() {
+            assert!(err.to_string().contains("unresolved type Tag"), "{}", err);
+        }
+        assert!(crate_model("blog", "pub struct User { id: u64 }")
+            .unresolved()
+            .is_empty());
+    }
+
+    #[test]
+    fn const_generic_array_lengths_keep_the_parameter() {
+        let named =
+            named_struct("struct Matrix<const N: usize> { data: [f32; N] }");
+        assert_eq!(
+            named.generics,
+            [RuaGenericParam::Const {
+                name: "N".to_owned(),
+                ty: RuaType::Usize
+            }]
+        );
+        assert_eq!(
+            *named.fields[0].ty,
+            RuaType::Array(RuaArray::new(
+                RuaType::F32,
+                RuaArrayLen::Const("N".to_owned())
+            ))
+        );
+    }
+
+    #[test]
+    fn path_array_lengths_keep_the_path() {
+        for (src, len) in [
+            ("[u8; Self::LEN]", "Self::LEN"),
+            ("[u8; consts::SIZE]", "consts::SIZE"),
+        ] {
+            assert_converts(
+                src,
+                &RuaType::Array(RuaArray::new(
+                    RuaType::U8,
+                    RuaArrayLen::Const(len.to_owned()),
+                )),
+            );
+        }
+    }
+
+    #[test]
+    fn unnamed_params_are_named_after_their_position() {
+        let item =
+            syn::parse_str::<syn::ItemFn>("fn f(_: i32, count: usize) {}")
+                .unwrap();
+        let f = RuaSigFn::try_from(&item).unwrap();
+        let params = f
+            .params
+            .iter()
+            .map(|param| (param.name.render(), param.ty.as_ref().clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(params, [("arg0", RuaType::I32), ("count", RuaType::Usize)]);
+    }
+
+    #[test]
+    fn references_keep_the_receiver_and_the_lifetime() {
+        let sig =
+            syn::parse_str::<syn::Signature>("fn name(&self) -> &str").unwrap();
+        let f = RuaSigFn::try_from(&sig).unwrap();
+        assert_eq!(
+            f.receiver.as_deref(),
+            Some(&RuaType::Reference(RuaReference::new(
+                false,
+                custom("Self", Vec::new())
+            )))
+        );
+        assert!(f.params.is_empty());
+        assert_eq!(
+            *f.ret,
+            RuaType::Reference(RuaReference::new(false, RuaType::Str))
+        );
+
+        let ty = convert_type("&'a str").unwrap();
+        let reference = match ty {
+            RuaType::Reference(reference) => reference,
+            ty => panic!("expected a reference, got {:?}", ty),
+        };
+        assert_eq!(reference.lifetime.as_deref(), Some("a"));
+        assert!(!reference.is_mut);
+        assert_eq!(*reference.ty, RuaType::Str);
+    }
+
+    #[test]
+    fn nested_references_flatten_into_one() {
+        let flat = |src| convert_type(src).unwrap().flatten_references();
+        let shared = RuaType::Reference(RuaReference::new(false, RuaType::I32));
+        assert_eq!(flat("&&i32"), shared);
+        // a mutable reference to a shared one cannot mutate the value
+        assert_eq!(flat("&mut &i32"), shared);
+        assert_eq!(
+            flat("&mut &mut i32"),
+            RuaType::Reference(RuaReference::new(true, RuaType::I32))
+        );
+    }
+
+    #[test]
+    fn dependencies_look_through_arrays_and_slices() {
+        let names = |deps: Vec<&RuaName>| {
+            deps.iter()
+                .map(|dep| dep.render().to_owned())
+                .collect::<Vec<_>>()
+        };
+        let ty = convert_type("[Widget; 8]").unwrap();
+        assert_eq!(names(ty.dependencies()), ["Widget"]);
+        let ty = convert_type("&[Bar]").unwrap();
+        assert_eq!(names(ty.dependencies()), ["Bar"]);
+        let s = convert_struct(
+            "struct Panel<'a, T> { items: [Widget; 8], bars: &'a [Bar], \
+             extra: T, more: [Widget; 2] }",
+        );
+        assert_eq!(names(s.dependencies()), ["Widget", "Bar"]);
+    }
+
+    #[test]
+    fn raw_field_names_are_renamed_or_escaped() {
+        let named = named_struct("struct Token { r#type: u8 }");
+        let field = &named.fields[0];
+        let case = RuaCase::CamelCase;
+        let mut naming = RuaNaming::new();
+        assert_eq!(field.rendered_name(&case, &naming), "type");
+        naming.reserve(["type"]);
+        assert_eq!(field.rendered_name(&case, &naming), "type_");
+        naming.rename("type", "kind");
+        assert_eq!(field.rendered_name(&case, &naming), "kind");
+        assert_eq!(naming.apply(&field.name, &case), "kind");
+    }
+
+    #[test]
+    fn every_variant_keeps_its_docs() {
+        let item = syn::parse_str::<syn::ItemEnum>(
+            "/// A shape.
+            enum Shape {
+                /// A circle.
+                Circle { radius: f64 },
+                /// A square.
+                /// With equal sides.
+                Square(f64),
+            }",
+        )
+        .unwrap();
+        let e = RuaEnum::try_from(item).unwrap();
+        assert_eq!(e.docs.as_deref(), Some("A shape."));
+        let docs = e.variants.iter().map(RuaStruct::docs).collect::<Vec<_>>();
+        assert_eq!(
+            docs,
+            [Some("A circle."), Some("A square.\nWith equal sides.")]
+        );
+    }
+
+    #[test]
+    fn non_exhaustive_enums_are_flagged() {
+        let convert = |src| {
+            RuaEnum::try_from(syn::parse_str::<syn::ItemEnum>(src).unwrap())
+                .unwrap()
+        };
+        assert!(convert("#[non_exhaustive] enum E { A, B }").is_non_exhaustive);
+        assert!(!convert("enum E { A, B }").is_non_exhaustive);
+    }
+
+    #[test]
+    fn transparent_newtypes_round_trip() {
+        let s = convert_struct("#[rua(transparent)] struct Meters(f64);");
+        match &s {
+            RuaStruct::Tuple(tuple) => assert!(tuple.is_transparent),
+            s => panic!("expected a tuple struct, got {:?}", s),
+        }
+        let rendered = crate::render::RuaRustRenderer.render_struct(&s);
+        assert_eq!(convert_struct(&rendered), s);
+    }
+
+    #[test]
+    fn c_like_enums_have_only_unit_variants() {
+        let convert = |src| {
+            RuaEnum::try_from(syn::parse_str::<syn::ItemEnum>(src).unwrap())
+                .unwrap()
+        };
+        assert!(convert("enum Color { Red, Green = 2, Blue }").is_c_like());
+        assert!(!convert("enum Value { Empty, Int(i32) }").is_c_like());
+    }
+
+    #[test]
+    fn same_identity_ignores_the_case() {
+        let foo_bar = RuaName::new("fooBar", RuaCase::CamelCase);
+        for (name, case) in [
+            ("foo_bar", RuaCase::SnakeCase),
+            ("FooBar", RuaCase::PascalCase),
+        ] {
+            let other = RuaName::new(name, case);
+            assert!(foo_bar.same_identity(&other), "{}", other);
+            assert!(other.same_identity(&foo_bar), "{}", other);
+        }
+        let foo_baz = RuaName::new("fooBaz", RuaCase::CamelCase);
+        assert!(!foo_bar.same_identity(&foo_baz));
+    }
+
+    #[test]
+    fn fields_keep_their_declaration_order() {
+        let named = named_struct(
+            "struct Header { version: u8, flags: u16, zeta: u32, \
+             alpha: u64, mid: i8, beta: bool }",
+        );
+        let names = named
+            .fields_in_order()
+            .iter()
+            .map(|field| field.name.render())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["version", "flags", "zeta", "alpha", "mid", "beta"]);
+    }
+
+    #[test]
+    fn slices_and_arrays_expose_their_element() {
+        let slice = RuaSlice::new(RuaType::U8);
+        assert_eq!(slice.element(), &RuaType::U8);
+        let arr = RuaArray::new(RuaType::U8, RuaArrayLen::Num(4));
+        assert_eq!(arr.element(), &RuaType::U8);
+        assert_eq!(arr.len, RuaArrayLen::Num(4));
+    }
+
+    #[test]
+    fn names_keep_the_case_they_are_created_with() {
+        for case in
+            [RuaCase::SnakeCase, RuaCase::CamelCase, RuaCase::PascalCase]
+        {
+            let name = RuaName::new(case.convert("foo_bar"), case.clone());
+            assert_eq!(name.case(), &case);
+        }
+    }
+
+    #[test]
+    fn render_gives_the_identifier_and_display_the_case() {
+        let name = RuaName::new("foo_bar", RuaCase::SnakeCase);
+        assert_eq!(name.render(), "foo_bar");
+        assert_eq!(name.to_string(), "snake_case(foo_bar)");
+    }
+
+    #[test]
+    fn convert_item_converts_traits() {
+        let item = syn::parse_str::<syn::Item>(
+            "trait Shape { fn area(&self) -> f64; }",
+        )
+        .unwrap();
+        let t = match convert_item(&item).unwrap() {
+            Some(RuaItem::Trait(t)) => t,
+            item => panic!("expected a trait, got {:?}", item),
+        };
+        assert_eq!(t.name.render(), "Shape");
+        assert_eq!(t.methods[0].name.render(), "area");
+        assert_eq!(*t.methods[0].ret, RuaType::F64);
+    }
+}
@@ -0,0 +1,272 @@
+//! This module contains the detection of recursive types without
+//! indirection, which cannot be represented and would make the generators
+//! loop forever.
+use std::collections::HashMap;
+
+use crate::{
+    errors::ConversionError,
+    models::{
+        resolve_module, RuaCustom, RuaItem, RuaNamed, RuaStruct, RuaType,
+    },
+};
+
+/// The standard library types that store their type arguments on the heap,
+/// so that a type can contain itself through them.
+const INDIRECTIONS: [&str; 4] = ["Box", "Rc", "Arc", "Vec"];
+
+/// Checks that none of the structs and enums contains itself without
+/// indirection, directly or through the others, e.g. `struct Node { next:
+/// Node }`. The items come with the path of the module declaring them
+/// relative to the crate root, e.g. `["shapes"]` for `crate::shapes`, and
+/// types are told apart by their full path, so `a::Node` and `b::Node` are
+/// different types. Items other than structs and enums are ignored.
+///
+/// References, pointers, slices and functions are indirections, and so are
+/// `Box`, `Rc`, `Arc` and `Vec`. The type arguments of other types, e.g.
+/// `Option<Node>`, are stored inline. Reports the first cycle found.
+pub fn check_recursive_types<'a>(
+    items: impl IntoIterator<Item = (&'a [String], &'a RuaItem)>,
+) -> Result<(), ConversionError> {
+    let types = items
+        .into_iter()
+        .filter(|(_, item)| {
+            matches!(item, RuaItem::Struct(_) | RuaItem::Enum(_))
+        })
+        .collect::<Vec<_>>();
+    let resolver = Resolver::new(&types);
+
+    let mut names = Vec::new();
+    let mut deps = HashMap::new();
+    for (module, item) in types.iter() {
+        let mut inline = Vec::new();
+        match item {
+            RuaItem::Struct(s) => {
+                collect_struct_deps(s, module, &resolver, &mut inline)
+            }
+            RuaItem::Enum(e) => {
+                for variant in e.variants.iter() {
+                    collect_struct_deps(
+                        variant,
+                        module,
+                        &resolver,
+                        &mut inline,
+                    );
+                }
+            }
+            _ => {}
+        }
+        let name = qualified_name(module, item.name().render());
+        names.push(name.clone());
+        deps.insert(name, inline);
+    }
+
+    let mut visited = HashMap::new();
+    for name in names.iter() {
+        let mut path = Vec::new();
+        if let Some(cycle) = find_cycle(name, &deps, &mut visited, &mut path) {
+            return Err(ConversionError::builder()
+                .source_type("RuaType")
+                .message(format!(
+                    "recursive type without indirection: {}",
+                    cycle.join(" -> ")
+                ))
+                .build());
+        }
+    }
+    Ok(())
+}
+
+/// Joins the module path and the name, e.g. `shapes::Circle`.
+fn qualified_name(module: &[String], name: &str) -> String {
+    module
+        .iter()
+        .map(String::as_str)
+        .chain(std::iter::once(name))
+        .collect::<Vec<_>>()
+        .join("::")
+}
+
+/// Resolves the names the types refer to into the full paths of the declared
+/// types.
+struct Resolver<'a> {
+    /// The modules declaring the types, keyed by the names of the types.
+    by_name: HashMap<&'a str, Vec<&'a [String]>>,
+}
+
+impl<'a> Resolver<'a> {
+    fn new(types: &[(&'a [String], &'a RuaItem)]) -> Self {
+        let mut by_name = HashMap::<_, Vec<_>>::new();
+        for (module, item) in types.iter() {
+            by_name
+                .entry(item.name().render())
+                .or_default()
+                .push(*module);
+        }
+        Self { by_name }
+    }
+
+    /// Returns the full path of the declared type the custom type refers to
+    /// from the module, if any, see [resolve_module].
+    fn resolve(&self, module: &[String], custom: &RuaCustom) -> Option<String> {
+        let name = custom.name.render();
+        let declared = self.by_name.get(name)?;
+        let found = resolve_module(declared, 0, module, &custom.path)?;
+        Some(qualified_name(found, name))
+    }
+}
+
+/// Collects the full paths of the types stored inline in the struct.
+fn collect_struct_deps(
+    s: &RuaStruct,
+    module: &[String],
+    resolver: &Resolver<'_>,
+    deps: &mut Vec<String>,
+) {
+    match s {
+        RuaStruct::Named(named) => {
+            for field in named.fields.iter() {
+                collect_type_deps(&field.ty, module, resolver, deps);
+            }
+        }
+        RuaStruct::Tuple(tuple) => {
+            for ty in tuple.tys.iter() {
+                collect_type_deps(ty, module, resolver, deps);
+            }
+        }
+        RuaStruct::Unit(_) => {}
+    }
+}
+
+/// Collects the full paths of the types stored inline in the type.
+fn collect_type_deps(
+    ty: &RuaType,
+    module: &[String],
+    resolver: &Resolver<'_>,
+    deps: &mut Vec<String>,
+) {
+    match ty {
+        RuaType::Custom(custom) if is_indirection(custom) => {}
+        RuaType::Custom(custom) => {
+            deps.extend(resolver.resolve(module, custom));
+            for arg in custom.args.iter() {
+                collect_type_deps(arg, module, resolver, deps);
+            }
+        }
+        RuaType::Struct(s) => {
+            deps.extend(resolver.resolve(module, &s.name().clone().into()))
+        }
+        RuaType::Enum(e) => {
+            deps.extend(resolver.resolve(module, &e.name.clone().into()))
+        }
+        RuaType::Array(arr) => {
+            collect_type_deps(&arr.ty, module, resolver, deps)
+        }
+        RuaType::Tuple(tuple) => {
+            for ty in tuple.tys.iter() {
+                collect_type_deps(ty, module, resolver, deps);
+            }
+        }
+        RuaType::NonZero(non_zero) => {
+            collect_type_deps(&non_zero.ty, module, resolver, deps)
+        }
+        _ => {}
+    }
+}
+
+/// Checks if the type is one of [INDIRECTIONS], unqualified or qualified
+/// with its module in `std` or `alloc`, e.g. `std::boxed::Box`.
+fn is_indirection(custom: &RuaCustom) -> bool {
+    let name = custom.name.render();
+    if !INDIRECTIONS.contains(&name) {
+        return false;
+    }
+    match custom.path.as_slice() {
+        [] => true,
+        [krate, _] => krate == "std" || krate == "alloc",
+        _ => false,
+    }
+}
+
+/// Searches for a cycle from the type, depth first. `visited` maps the types
+/// to whether they are done, i.e. known not to be part of a cycle. Returns
+/// the cycle as the names along it, starting and ending with the same one.
+fn find_cycle(
+    name: &str,
+    deps: &HashMap<String, Vec<String>>,
+    visited: &mut HashMap<String, bool>,
+    path: &mut Vec<String>,
+) -> Option<Vec<String>> {
+    match visited.get(name) {
+        Some(true) => return None,
+        Some(false) => {
+            let start = path.iter().position(|n| n == name)?;
+            let mut cycle = path[start..].to_vec();
+            cycle.push(name.to_owned());
+            return Some(cycle);
+        }
+        None => {}
+    }
+    let inline = deps.get(name)?;
+    visited.insert(name.to_owned(), false);
+    path.push(name.to_owned());
+    for dep in inline.iter() {
+        if let Some(cycle) = find_cycle(dep, deps, visited, path) {
+            return Some(cycle);
+        }
+    }
+    path.pop();
+    visited.insert(name.to_owned(), true);
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::convert_file;
+
+    /// Converts the items of the source, declared in the module.
+    fn items(module: &[&str], src: &str) -> Vec<(Vec<String>, RuaItem)> {
+        let module = module.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        convert_file(src, None)
+            .into_iter()
+            .map(|item| (module.clone(), item))
+            .collect()
+    }
+
+    fn check(items: &[(Vec<String>, RuaItem)]) -> Result<(), ConversionError> {
+        check_recursive_types(
+            items.iter().map(|(module, item)| (module.as_slice(), item)),
+        )
+    }
+
+    #[test]
+    fn recursion_through_box_is_allowed() {
+        let items = items(&[], "struct A { b: Box<B> } struct B { a: A }");
+        assert!(check(&items).is_ok());
+    }
+
+    #[test]
+    fn recursion_through_a_bare_field_is_reported() {
+        let items = items(&[], "struct A { b: B } enum B { A(A), None }");
+        let err = check(&items).unwrap_err();
+        assert!(err.to_string().contains("A -> B -> A"), "{}", err);
+    }
+
+    #[test]
+    fn option_is_not_an_indirection() {
+        let inline = items(&[], "struct Node { next: Option<Node> }");
+        assert!(check(&inline).is_err());
+        let boxed = items(&[], "struct Node { next: Option<Box<Node>> }");
+        assert!(check(&boxed).is_ok());
+    }
+
+    #[test]
+    fn types_are_told_apart_by_module() {
+        let mut all = items(&["a"], "struct Node { next: super::b::Node }");
+        all.extend(items(&["b"], "struct Node { value: u32 }"));
+        assert!(check(&all).is_ok());
+        all.extend(items(&["b"], "struct Leaf { node: crate::b::Leaf }"));
+        let err = check(&all).unwrap_err();
+        assert!(err.to_string().contains("b::Leaf -> b::Leaf"), "{}", err);
+    }
+}
@@ -0,0 +1,891 @@
+//! This module contains the logic for rendering types in a target language.
+use std::{cell::RefCell, collections::HashMap, fmt::Display};
+
+use crate::models::{
+    RuaArrayLen, RuaCustom, RuaEnum, RuaFn, RuaGenericParam, RuaNamed,
+    RuaSigFn, RuaStruct, RuaType, RuaVar,
+};
+
+/// How integers wider than 32 bits are rendered, for targets that cannot
+/// represent them natively, e.g. `number` in JavaScript.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub enum RuaWideIntPolicy {
+    /// Renders them with [RuaTypeRenderer::render_leaf] like other integers.
+    #[default]
+    Native,
+    /// Renders all of them as the given type, e.g. `bigint` or `string`.
+    As(String),
+}
+
+/// How `char`, a 4-byte Unicode scalar value, is rendered, for targets
+/// without an equivalent type, e.g. Dart.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub enum RuaCharPolicy {
+    /// Renders it with [RuaTypeRenderer::render_leaf] like other types.
+    #[default]
+    Native,
+    /// Renders it as the given type, e.g. `int` in Dart or `uint32_t` in C
+    /// for the code point, or `String` in Dart for the character itself.
+    As(String),
+}
+
+/// How `str`, borrowed, and `String`, owned, are told apart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum RuaStrPolicy {
+    /// Renders them separately with [RuaTypeRenderer::render_leaf], e.g. for
+    /// FFI targets, where a borrowed `&str` is a pointer and a length while
+    /// an owned `String` must be freed.
+    #[default]
+    Distinct,
+    /// Renders `str` and references to it like `String`, e.g. for high-level
+    /// targets with a single string type.
+    Collapse,
+}
+
+/// A table mapping well-known standard library types, which convert to
+/// [RuaType::Custom], e.g. `std::time::Duration`, `std::time::SystemTime`
+/// and `std::time::Instant`, to the types of a target language, e.g.
+/// `Duration` in Dart or `int64_t` in C for the nanoseconds.
+///
+/// The types are keyed by their full path, so that a type of the crate with
+/// the same name, e.g. `crate::units::Duration`, is not mapped. `std`, `core`
+/// and `alloc` are interchangeable.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RuaStdTypeMap {
+    types: HashMap<Vec<String>, String>,
+}
+
+impl RuaStdTypeMap {
+    /// Creates an empty table, where no type is mapped.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Maps the type with the full path, e.g. `std::time::Duration`, to the
+    /// target type, replacing the previous mapping if any.
+    pub fn insert(
+        &mut self,
+        path: impl AsRef<str>,
+        target: impl AsRef<str>,
+    ) -> &mut Self {
+        self.types
+            .insert(std_path(path.as_ref()), target.as_ref().to_owned());
+        self
+    }
+
+    /// Removes the mapping of the type with the full path, if any.
+    pub fn remove(&mut self, path: impl AsRef<str>) -> &mut Self {
+        self.types.remove(&std_path(path.as_ref()));
+        self
+    }
+
+    /// Returns the target type the custom type is mapped to, if any. Since
+    /// `use` declarations are not modeled, a type also matches the only full
+    /// path that ends with its own, e.g. `Duration` and `time::Duration`
+    /// match `std::time::Duration`, but `units::Duration` does not.
+    pub fn get(&self, custom: &RuaCustom) -> Option<&str> {
+        let path = std_path(&custom.qualified_name());
+        if let Some(target) = self.types.get(&path) {
+            return Some(target);
+        }
+        let mut matches = self
+            .types
+            .iter()
+            .filter(|(full, _)| full.ends_with(&path))
+            .map(|(_, target)| target.as_str());
+        match (matches.next(), matches.next()) {
+            (Some(target), None) => Some(target),
+            _ => None,
+        }
+    }
+}
+
+/// Splits the path into its segments, replacing a leading `core` or `alloc`
+/// with `std`, which re-exports them.
+fn std_path(path: &str) -> Vec<String> {
+    path.split("::")
+        .enumerate()
+        .map(|(i, segment)| match segment {
+            "core" | "alloc" if i == 0 => "std".to_owned(),
+            _ => segment.to_owned(),
+        })
+        .collect()
+}
+
+/// Implement this trait to map [RuaType]s to the types of a target language.
+///
+/// Only the leaves need to be implemented. Slices, arrays, tuples, references,
+/// pointers and non-zero integers are taken apart by [RuaTypeRenderer::render],
+/// which renders their element types recursively before handing them to the
+/// corresponding `render_*` method. By default, those methods produce Rust
+/// syntax.
+pub trait RuaTypeRenderer {
+    /// Renders a type that is not a slice, array, tuple, reference, pointer or
+    /// non-zero integer.
+    fn render_leaf(&self, ty: &RuaType) -> String;
+
+    /// Returns how `i64`, `u64`, `i128`, `u128`, `isize` and `usize` are
+    /// rendered.
+    fn wide_int_policy(&self) -> RuaWideIntPolicy {
+        RuaWideIntPolicy::Native
+    }
+
+    /// Returns how `char` is rendered.
+    fn char_policy(&self) -> RuaCharPolicy {
+        RuaCharPolicy::Native
+    }
+
+    /// Returns how `str` and `String` are told apart.
+    fn str_policy(&self) -> RuaStrPolicy {
+        RuaStrPolicy::Distinct
+    }
+
+    /// Returns the target type of a [RuaType::Custom] type that is mapped,
+    /// e.g. with a [RuaStdTypeMap]. By default, nothing is mapped.
+    fn std_type(&self, _custom: &RuaCustom) -> Option<String> {
+        None
+    }
+
+    /// Renders a slice, given its rendered element type.
+    fn render_slice(&self, elem: String) -> String {
+        format!("[{}]", elem)
+    }
+
+    /// Renders an array, given its rendered element type and its length.
+    fn render_array(&self, elem: String, len: &RuaArrayLen) -> String {
+        match len {
+            RuaArrayLen::Num(n) => format!("[{}; {}]", elem, n),
+            RuaArrayLen::Const(name) => format!("[{}; {}]", elem, name),
+        }
+    }
+
+    /// Renders a tuple, given its rendered element types.
+    fn render_tuple(&self, elems: Vec<String>) -> String {
+        if elems.len() == 1 {
+            format!("({},)", elems[0])
+        } else {
+            format!("({})", elems.join(", "))
+        }
+    }
+
+    /// Renders a reference, given the rendered type it points to.
+    fn render_reference(&self, is_mut: bool, elem: String) -> String {
+        if is_mut {
+            format!("&mut {}", elem)
+        } else {
+            format!("&{}", elem)
+        }
+    }
+
+    /// Renders a reference to an array, e.g. `&[u8; 32]`, given the rendered
+    /// element type and the length of the array. Override it to map such
+    /// references to fixed-size buffers, e.g. `const uint8_t (*)[32]` in C.
+    fn render_array_reference(
+        &self,
+        is_mut: bool,
+        elem: String,
+        len: &RuaArrayLen,
+    ) -> String {
+        self.render_reference(is_mut, self.render_array(elem, len))
+    }
+
+    /// Renders a pointer, given the rendered type it points to.
+    fn render_pointer(&self, is_const: bool, elem: String) -> String {
+        if is_const {
+            format!("*const {}", elem)
+        } else {
+            format!("*mut {}", elem)
+        }
+    }
+
+    /// Renders a non-null pointer, given the rendered type it points to.
+    fn render_non_null(&self, elem: String) -> String {
+        format!("NonNull<{}>", elem)
+    }
+
+    /// Renders a non-zero integer, given its rendered integer type.
+    fn render_non_zero(&self, elem: String) -> String {
+        format!("NonZero<{}>", elem)
+    }
+
+    /// Renders the type, delegating the composite types to the `render_*`
+    /// methods, wide integers to [RuaTypeRenderer::wide_int_policy], `char`
+    /// to [RuaTypeRenderer::char_policy], strings to
+    /// [RuaTypeRenderer::str_policy], mapped custom types to
+    /// [RuaTypeRenderer::std_type] and everything else to
+    /// [RuaTypeRenderer::render_leaf].
+    fn render(&self, ty: &RuaType) -> String {
+        render_type(self, ty)
+    }
+}
+
+/// Renders the type as [RuaTypeRenderer::render] does by default, rendering
+/// the nested types with `renderer.render`. Renderers that override `render`,
+/// e.g. to memoize it, can call this to take the types apart.
+pub fn render_type<R: RuaTypeRenderer + ?Sized>(
+    renderer: &R,
+    ty: &RuaType,
+) -> String {
+    match ty {
+        RuaType::Slice(slice) => {
+            renderer.render_slice(renderer.render(&slice.ty))
+        }
+        RuaType::Array(arr) => {
+            renderer.render_array(renderer.render(&arr.ty), &arr.len)
+        }
+        RuaType::Tuple(tuple) => renderer.render_tuple(
+            tuple.tys.iter().map(|ty| renderer.render(ty)).collect(),
+        ),
+        RuaType::Reference(reference) => match reference.ty.as_ref() {
+            RuaType::Str if renderer.str_policy() == RuaStrPolicy::Collapse => {
+                renderer.render_leaf(&RuaType::String)
+            }
+            RuaType::Array(arr) => renderer.render_array_reference(
+                reference.is_mut,
+                renderer.render(&arr.ty),
+                &arr.len,
+            ),
+            _ => renderer.render_reference(
+                reference.is_mut,
+                renderer.render(&reference.ty),
+            ),
+        },
+        RuaType::Pointer(ptr) if ptr.is_non_null => {
+            renderer.render_non_null(renderer.render(&ptr.ty))
+        }
+        RuaType::Pointer(ptr) => {
+            renderer.render_pointer(ptr.is_const, renderer.render(&ptr.ty))
+        }
+        RuaType::NonZero(non_zero) => {
+            renderer.render_non_zero(renderer.render(&non_zero.ty))
+        }
+        RuaType::I64
+        | RuaType::U64
+        | RuaType::I128
+        | RuaType::U128
+        | RuaType::Isize
+        | RuaType::Usize => match renderer.wide_int_policy() {
+            RuaWideIntPolicy::Native => renderer.render_leaf(ty),
+            RuaWideIntPolicy::As(name) => name,
+        },
+        RuaType::Char => match renderer.char_policy() {
+            RuaCharPolicy::Native => renderer.render_leaf(ty),
+            RuaCharPolicy::As(name) => name,
+        },
+        RuaType::Str if renderer.str_policy() == RuaStrPolicy::Collapse => {
+            renderer.render_leaf(&RuaType::String)
+        }
+        RuaType::Custom(custom) => match renderer.std_type(custom) {
+            Some(target) => target,
+            None => renderer.render_leaf(ty),
+        },
+        _ => renderer.render_leaf(ty),
+    }
+}
+
+/// Wraps a [RuaTypeRenderer] to memoize the rendered types, so that types
+/// appearing many times, e.g. `String`, are only rendered once, including
+/// when nested in other types. The renderer must render equal types to the
+/// same string.
+#[derive(Debug, Clone, Default)]
+pub struct RuaCachedRenderer<R> {
+    inner: R,
+    cache: RefCell<HashMap<RuaType, String>>,
+}
+
+impl<R: RuaTypeRenderer> RuaCachedRenderer<R> {
+    /// Wraps the renderer with an empty cache.
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            cache: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the wrapped renderer.
+    pub fn inner(&self) -> &R {
+        &self.inner
+    }
+
+    /// Returns the number of types in the cache.
+    pub fn cached_len(&self) -> usize {
+        self.cache.borrow().len()
+    }
+
+    /// Empties the cache.
+    pub fn clear(&self) {
+        self.cache.borrow_mut().clear();
+    }
+}
+
+impl<R: RuaTypeRenderer> RuaTypeRenderer for RuaCachedRenderer<R> {
+    fn render_leaf(&self, ty: &RuaType) -> String {
+        self.inner.render_leaf(ty)
+    }
+
+    fn wide_int_policy(&self) -> RuaWideIntPolicy {
+        self.inner.wide_int_policy()
+    }
+
+    fn char_policy(&self) -> RuaCharPolicy {
+        self.inner.char_policy()
+    }
+
+    fn str_policy(&self) -> RuaStrPolicy {
+        self.inner.str_policy()
+    }
+
+    fn std_type(&self, custom: &RuaCustom) -> Option<String> {
+        self.inner.std_type(custom)
+    }
+
+    fn render_slice(&self, elem: String) -> String {
+        self.inner.render_slice(elem)
+    }
+
+    fn render_array(&self, elem: String, len: &RuaArrayLen) -> String {
+        self.inner.render_array(elem, len)
+    }
+
+    fn render_tuple(&self, elems: Vec<String>) -> String {
+        self.inner.render_tuple(elems)
+    }
+
+    fn render_reference(&self, is_mut: bool, elem: String) -> String {
+        self.inner.render_reference(is_mut, elem)
+    }
+
+    fn render_array_reference(
+        &self,
+        is_mut: bool,
+        elem: String,
+        len: &RuaArrayLen,
+    ) -> String {
+        self.inner.render_array_reference(is_mut, elem, len)
+    }
+
+    fn render_pointer(&self, is_const: bool, elem: String) -> String {
+        self.inner.render_pointer(is_const, elem)
+    }
+
+    fn render_non_null(&self, elem: String) -> String {
+        self.inner.render_non_null(elem)
+    }
+
+    fn render_non_zero(&self, elem: String) -> String {
+        self.inner.render_non_zero(elem)
+    }
+
+    fn render(&self, ty: &RuaType) -> String {
+        if let Some(rendered) = self.cache.borrow().get(ty) {
+            return rendered.clone();
+        }
+        // the nested types are rendered through `self`, so they are cached
+        let rendered = render_type(self, ty);
+        self.cache.borrow_mut().insert(ty.clone(), rendered.clone());
+        rendered
+    }
+}
+
+/// Renders the models back to approximate Rust source. Attributes other than
+/// `#[rua(default = "...")]`, `#[rua(transparent)]` and `#[non_exhaustive]`,
+/// visibility, lifetimes and function bodies are not part of the models, so
+/// they are not rendered.
+/// This is meant for debugging and golden tests, where the rendered source is
+/// easier to compare than the models themselves.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct RuaRustRenderer;
+
+impl RuaTypeRenderer for RuaRustRenderer {
+    fn render_leaf(&self, ty: &RuaType) -> String {
+        match ty {
+            RuaType::I8 => "i8".to_owned(),
+            RuaType::I16 => "i16".to_owned(),
+            RuaType::I32 => "i32".to_owned(),
+            RuaType::I64 => "i64".to_owned(),
+            RuaType::I128 => "i128".to_owned(),
+            RuaType::U8 => "u8".to_owned(),
+            RuaType::U16 => "u16".to_owned(),
+            RuaType::U32 => "u32".to_owned(),
+            RuaType::U64 => "u64".to_owned(),
+            RuaType::U128 => "u128".to_owned(),
+            RuaType::F32 => "f32".to_owned(),
+            RuaType::F64 => "f64".to_owned(),
+            RuaType::Bool => "bool".to_owned(),
+            RuaType::Isize => "isize".to_owned(),
+            RuaType::Usize => "usize".to_owned(),
+            RuaType::Char => "char".to_owned(),
+            RuaType::Str => "str".to_owned(),
+            RuaType::String => "String".to_owned(),
+            RuaType::Struct(s) => s.name().render().to_owned(),
+            RuaType::Enum(e) => e.name.render().to_owned(),
+            RuaType::Fn(RuaFn::Bare(f)) => format!(
+                "fn({}){}",
+                f.params
+                    .iter()
+                    .map(|ty| self.render(ty))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                self.render_ret(&f.ret)
+            ),
+            RuaType::Fn(RuaFn::Fn(f)) => f.name.render().to_owned(),
+            RuaType::Custom(custom) if custom.args.is_empty() => {
+                custom.qualified_name()
+            }
+            RuaType::Custom(custom) => format!(
+                "{}<{}>",
+                custom.qualified_name(),
+                custom
+                    .args
+                    .iter()
+                    .map(|ty| self.render(ty))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            RuaType::Unit => "()".to_owned(),
+            // The composite types are usually taken apart by `render`, but are
+            // rendered the same way when passed here directly.
+            RuaType::Slice(slice) => self.render_slice(self.render(&slice.ty)),
+            RuaType::Array(arr) => {
+                self.render_array(self.render(&arr.ty), &arr.len)
+            }
+            RuaType::Tuple(tuple) => self.render_tuple(
+                tuple.tys.iter().map(|ty| self.render(ty)).collect(),
+            ),
+            RuaType::Reference(reference) => self
+                .render_reference(reference.is_mut, self.render(&reference.ty)),
+            RuaType::Pointer(ptr) if ptr.is_non_null => {
+                self.render_non_null(self.render(&ptr.ty))
+            }
+            RuaType::Pointer(ptr) => {
+                self.render_pointer(ptr.is_const, self.render(&ptr.ty))
+            }
+            RuaType::NonZero(non_zero) => {
+                self.render_non_zero(self.render(&non_zero.ty))
+            }
+        }
+    }
+}
+
+impl RuaRustRenderer {
+    /// Renders a return type, i.e. ` -> T`, or nothing for `()`.
+    fn render_ret(&self, ret: &RuaType) -> String {
+        match ret {
+            RuaType::Unit => String::new(),
+            ret => format!(" -> {}", self.render(ret)),
+        }
+    }
+
+    /// Renders the generic parameters, i.e. `<T, const N: usize>`, or nothing
+    /// if there are none.
+    fn render_generics(&self, generics: &[RuaGenericParam]) -> String {
+        if generics.is_empty() {
+            return String::new();
+        }
+        let params = generics
+            .iter()
+            .map(|param| match param {
+                RuaGenericParam::Type { name } => name.clone(),
+                RuaGenericParam::Const { name, ty } => {
+                    format!("const {}: {}", name, self.render(ty))
+                }
+            })
+            .collect::<Vec<_>>();
+        format!("<{}>", params.join(", "))
+    }
+
+    /// Renders a field, including its default value.
+    fn render_field(&self, field: &RuaVar) -> String {
+        let default = match &field.default {
+            Some(default) => format!("#[rua(default = {:?})] ", default),
+            None => String::new(),
+        };
+        format!(
+            "{}{}: {}",
+            default,
+            field.name.render(),
+            self.render(&field.ty)
+        )
+    }
+
+    /// Renders the attributes of a struct or an enum variant.
+    fn render_attrs(&self, s: &RuaStruct) -> &'static str {
+        match s {
+            RuaStruct::Named(named) => {
+                render_non_exhaustive(named.is_non_exhaustive)
+            }
+            RuaStruct::Tuple(tuple) if tuple.is_transparent => {
+                "#[rua(transparent)] "
+            }
+            _ => "",
+        }
+    }
+
+    /// Renders a struct or an enum variant, without the `struct` keyword and
+    /// the trailing `;`.
+    fn render_struct_body(&self, s: &RuaStruct) -> String {
+        match s {
+            RuaStruct::Named(named) => format!(
+                "{}{} {{ {} }}",
+                named.name.render(),
+                self.render_generics(&named.generics),
+                named
+                    .fields
+                    .iter()
+                    .map(|field| self.render_field(field))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            RuaStruct::Tuple(tuple) => format!(
+                "{}{}({})",
+                tuple.name.render(),
+                self.render_generics(&tuple.generics),
+                tuple
+                    .tys
+                    .iter()
+                    .map(|ty| self.render(ty))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            RuaStruct::Unit(unit) => {
+                format!(
+                    "{}{}",
+                    unit.name.render(),
+                    self.render_generics(&unit.generics)
+                )
+            }
+        }
+    }
+
+    /// Renders the struct as a Rust item.
+    pub fn render_struct(&self, s: &RuaStruct) -> String {
+        match s {
+            RuaStruct::Named(_) => format!(
+                "{}struct {}",
+                self.render_attrs(s),
+                self.render_struct_body(s)
+            ),
+            _ => format!(
+                "{}struct {};",
+                self.render_attrs(s),
+                self.render_struct_body(s)
+            ),
+        }
+    }
+
+    /// Renders the enum as a Rust item.
+    pub fn render_enum(&self, e: &RuaEnum) -> String {
+        format!(
+            "{}enum {}{} {{ {} }}",
+            render_non_exhaustive(e.is_non_exhaustive),
+            e.name.render(),
+            self.render_generics(&e.generics),
+            e.variants
+                .iter()
+                .map(|variant| format!(
+                    "{}{}",
+                    self.render_attrs(variant),
+                    self.render_struct_body(variant)
+                ))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    }
+
+    /// Renders the function as a Rust item with an empty body.
+    pub fn render_sig_fn(&self, f: &RuaSigFn) -> String {
+        let receiver = f
+            .receiver
+            .iter()
+            .map(|receiver| format!("self: {}", self.render(receiver)));
+        let params = f.params.iter().map(|param| {
+            format!("{}: {}", param.name.render(), self.render(&param.ty))
+        });
+        format!(
+            "fn {}{}({}){} {{}}",
+            f.name.render(),
+            self.render_generics(&f.generics),
+            receiver.chain(params).collect::<Vec<_>>().join(", "),
+            self.render_ret(&f.ret)
+        )
+    }
+}
+
+/// Renders `#[non_exhaustive]` with a trailing space if the flag is set.
+fn render_non_exhaustive(is_non_exhaustive: bool) -> &'static str {
+    if is_non_exhaustive {
+        "#[non_exhaustive] "
+    } else {
+        ""
+    }
+}
+
+impl Display for RuaType {
+    /// Formats the type as Rust source, see [RuaRustRenderer].
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", RuaRustRenderer.render(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+    use crate::test_support::{convert_type, representative_types};
+
+    /// Counts the leaves it renders.
+    #[derive(Default)]
+    struct CountingRenderer {
+        leaves: Cell<usize>,
+    }
+
+    impl RuaTypeRenderer for CountingRenderer {
+        fn render_leaf(&self, ty: &RuaType) -> String {
+            self.leaves.set(self.leaves.get() + 1);
+            RuaRustRenderer.render_leaf(ty)
+        }
+    }
+
+    /// Renders the types like [RuaRustRenderer], except the mapped ones.
+    struct MappingRenderer {
+        std_types: RuaStdTypeMap,
+    }
+
+    impl RuaTypeRenderer for MappingRenderer {
+        fn render_leaf(&self, ty: &RuaType) -> String {
+            RuaRustRenderer.render_leaf(ty)
+        }
+
+        fn std_type(&self, custom: &RuaCustom) -> Option<String> {
+            self.std_types.get(custom).map(str::to_owned)
+        }
+    }
+
+    #[test]
+    fn duration_fields_render_as_the_configured_type() {
+        let mut std_types = RuaStdTypeMap::new();
+        std_types
+            .insert("std::time::Duration", "Duration")
+            .insert("std::time::Instant", "DateTime");
+        let renderer = MappingRenderer { std_types };
+        let item = syn::parse_str::<syn::ItemStruct>(
+            "struct Timer { a: Duration, b: std::time::Duration, \
+             c: core::time::Duration, d: time::Instant, \
+             e: units::Duration, f: [Instant; 2] }",
+        )
+        .unwrap();
+        let fields = match RuaStruct::try_from(&item).unwrap() {
+            RuaStruct::Named(named) => named.fields,
+            _ => panic!("expected a named struct"),
+        };
+        let rendered = fields
+            .iter()
+            .map(|field| renderer.render(&field.ty))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            rendered,
+            [
+                "Duration",
+                "Duration",
+                "Duration",
+                "DateTime",
+                "units::Duration",
+                "[DateTime; 2]"
+            ]
+        );
+    }
+
+    /// Renders the types like [RuaRustRenderer], with the string policy.
+    struct StrRenderer(RuaStrPolicy);
+
+    impl RuaTypeRenderer for StrRenderer {
+        fn render_leaf(&self, ty: &RuaType) -> String {
+            RuaRustRenderer.render_leaf(ty)
+        }
+
+        fn str_policy(&self) -> RuaStrPolicy {
+            self.0
+        }
+    }
+
+    #[test]
+    fn str_policy_tells_strings_apart_or_collapses_them() {
+        let render = |policy, src| {
+            StrRenderer(policy).render(&convert_type(src).unwrap())
+        };
+        let distinct = RuaStrPolicy::Distinct;
+        assert_eq!(render(distinct, "str"), "str");
+        assert_eq!(render(distinct, "&str"), "&str");
+        assert_eq!(render(distinct, "&mut str"), "&mut str");
+        assert_eq!(render(distinct, "String"), "String");
+        let collapse = RuaStrPolicy::Collapse;
+        assert_eq!(render(collapse, "str"), "String");
+        assert_eq!(render(collapse, "&str"), "String");
+        assert_eq!(render(collapse, "&mut str"), "String");
+        assert_eq!(render(collapse, "String"), "String");
+        assert_eq!(render(collapse, "&String"), "&String");
+    }
+
+    /// Renders the types like [RuaRustRenderer], with the integer and `char`
+    /// policies.
+    #[derive(Default)]
+    struct PolicyRenderer {
+        wide_ints: RuaWideIntPolicy,
+        chars: RuaCharPolicy,
+    }
+
+    impl RuaTypeRenderer for PolicyRenderer {
+        fn render_leaf(&self, ty: &RuaType) -> String {
+            RuaRustRenderer.render_leaf(ty)
+        }
+
+        fn wide_int_policy(&self) -> RuaWideIntPolicy {
+            self.wide_ints.clone()
+        }
+
+        fn char_policy(&self) -> RuaCharPolicy {
+            self.chars.clone()
+        }
+    }
+
+    #[test]
+    fn wide_int_policy_renders_wide_integers_as_the_type() {
+        let render = |policy: &str, src| {
+            let renderer = PolicyRenderer {
+                wide_ints: RuaWideIntPolicy::As(policy.to_owned()),
+                ..Default::default()
+            };
+            renderer.render(&convert_type(src).unwrap())
+        };
+        assert_eq!(render("bigint", "i64"), "bigint");
+        assert_eq!(render("string", "i64"), "string");
+        assert_eq!(render("bigint", "(u128, usize)"), "(bigint, bigint)");
+        assert_eq!(render("bigint", "i32"), "i32");
+        let native = PolicyRenderer::default();
+        assert_eq!(native.render(&RuaType::I64), "i64");
+    }
+
+    #[test]
+    fn char_policy_renders_char_as_the_type() {
+        let render = |policy: &str, src| {
+            let renderer = PolicyRenderer {
+                chars: RuaCharPolicy::As(policy.to_owned()),
+                ..Default::default()
+            };
+            renderer.render(&convert_type(src).unwrap())
+        };
+        assert_eq!(render("int", "char"), "int");
+        assert_eq!(render("uint32_t", "char"), "uint32_t");
+        assert_eq!(render("int", "[char; 2]"), "[int; 2]");
+        assert_eq!(render("int", "u32"), "u32");
+        let native = PolicyRenderer::default();
+        assert_eq!(native.render(&RuaType::Char), "char");
+    }
+
+    /// Renders the length of an array in C.
+    fn c_len(len: &RuaArrayLen) -> String {
+        match len {
+            RuaArrayLen::Num(n) => n.to_string(),
+            RuaArrayLen::Const(name) => name.clone(),
+        }
+    }
+
+    /// Renders the types in C, overriding the composite types.
+    struct CRenderer;
+
+    impl RuaTypeRenderer for CRenderer {
+        fn render_leaf(&self, ty: &RuaType) -> String {
+            match ty {
+                RuaType::U8 => "uint8_t".to_owned(),
+                RuaType::I32 => "int32_t".to_owned(),
+                RuaType::Custom(custom) => {
+                    format!("struct {}", custom.name.render())
+                }
+                ty => RuaRustRenderer.render_leaf(ty),
+            }
+        }
+
+        fn render_array(&self, elem: String, len: &RuaArrayLen) -> String {
+            format!("{}[{}]", elem, c_len(len))
+        }
+
+        fn render_reference(&self, is_mut: bool, elem: String) -> String {
+            if is_mut {
+                format!("{}*", elem)
+            } else {
+                format!("const {}*", elem)
+            }
+        }
+
+        fn render_array_reference(
+            &self,
+            is_mut: bool,
+            elem: String,
+            len: &RuaArrayLen,
+        ) -> String {
+            let constness = if is_mut { "" } else { "const " };
+            format!("{}{} (*)[{}]", constness, elem, c_len(len))
+        }
+    }
+
+    #[test]
+    fn array_references_render_through_the_override() {
+        let item = syn::parse_str::<syn::ItemFn>(
+            "fn hash(key: &[u8; 32], n: &i32) {}",
+        )
+        .unwrap();
+        let f = RuaSigFn::try_from(&item).unwrap();
+        let rendered = f
+            .params
+            .iter()
+            .map(|param| CRenderer.render(&param.ty))
+            .collect::<Vec<_>>();
+        assert_eq!(rendered, ["const uint8_t (*)[32]", "const int32_t*"]);
+        let ty = convert_type("&mut [u8; N]").unwrap();
+        assert_eq!(CRenderer.render(&ty), "uint8_t (*)[N]");
+    }
+
+    #[test]
+    fn composite_types_delegate_their_elements() {
+        // the array takes the rendered reference, which takes the leaf
+        let ty = convert_type("[&Foo; 2]").unwrap();
+        assert_eq!(CRenderer.render(&ty), "const struct Foo*[2]");
+        let ty = convert_type("[&mut [i32; 3]; 2]").unwrap();
+        assert_eq!(CRenderer.render(&ty), "int32_t (*)[3][2]");
+    }
+
+    #[test]
+    fn struct_round_trips_through_rust_source() {
+        let src = "struct Point<T> { #[rua(default = \"0\")] x: i32, \
+                   tag: Option<T>, data: [u8; 4] }";
+        let item = syn::parse_str::<syn::ItemStruct>(src).unwrap();
+        let s = RuaStruct::try_from(&item).unwrap();
+        let rendered = RuaRustRenderer.render_struct(&s);
+        let again = syn::parse_str::<syn::ItemStruct>(&rendered)
+            .unwrap_or_else(|err| {
+                panic!("{:?} does not parse: {}", rendered, err)
+            });
+        assert_eq!(RuaStruct::try_from(&again).unwrap(), s);
+    }
+
+    #[test]
+    fn render_leaf_renders_every_type() {
+        for (src, ty) in representative_types() {
+            let rendered = RuaRustRenderer.render_leaf(&ty);
+            assert_eq!(convert_type(&rendered).unwrap(), ty, "{}", src);
+        }
+    }
+
+    #[test]
+    fn cached_renderer_hits_nested_types() {
+        let renderer = RuaCachedRenderer::new(CountingRenderer::default());
+        let ty = convert_type("(String, [String; 2], &String)").unwrap();
+        assert_eq!(renderer.render(&ty), "(String, [String; 2], &String)");
+        // `String` is rendered once, then taken from the cache
+        assert_eq!(renderer.inner().leaves.get(), 1);
+        assert_eq!(renderer.cached_len(), 4);
+        renderer.render(&ty);
+        assert_eq!(renderer.inner().leaves.get(), 1);
+    }
+}
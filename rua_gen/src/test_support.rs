@@ -0,0 +1,187 @@
+//! Helpers for testing the conversions from `syn`, for `rua_gen` and the
+//! platform crates alike. Enabled in the tests of `rua_gen` and with the
+//! `test-support` feature.
+use crate::{
+    cfg::RuaCfg,
+    errors::ConversionError,
+    logic::convert_items,
+    models::{
+        RuaArray, RuaArrayLen, RuaBareFn, RuaCase, RuaCustom, RuaFn, RuaItem,
+        RuaName, RuaNonZero, RuaPointer, RuaReference, RuaSlice, RuaTuple,
+        RuaType,
+    },
+    render::{RuaRustRenderer, RuaTypeRenderer},
+};
+
+/// Parses the type from Rust source with `syn` and converts it.
+///
+/// # Panics
+///
+/// Panics if the source is not a valid type.
+#[track_caller]
+pub fn convert_type(src: &str) -> Result<RuaType, ConversionError> {
+    let ty = syn::parse_str::<syn::Type>(src)
+        .unwrap_or_else(|err| panic!("invalid type {:?}: {}", src, err));
+    (&ty).try_into()
+}
+
+/// Parses the items of a file from Rust source with `syn` and converts them
+/// with [convert_items], e.g. to build the models of a crate.
+///
+/// # Panics
+///
+/// Panics if the source is not a valid file or an item fails to convert.
+#[track_caller]
+pub fn convert_file(src: &str, cfg: Option<&RuaCfg>) -> Vec<RuaItem> {
+    let file = syn::parse_file(src)
+        .unwrap_or_else(|err| panic!("invalid file {:?}: {}", src, err));
+    convert_items(&file.items, cfg)
+        .unwrap_or_else(|err| panic!("failed to convert {:?}: {}", src, err))
+}
+
+/// Asserts that the type converts to `expected`, e.g. `&[u8]` to a reference
+/// to a slice of [RuaType::U8].
+///
+/// # Panics
+///
+/// Panics if the source is not a valid type, fails to convert or converts to
+/// another model.
+#[track_caller]
+pub fn assert_converts(src: &str, expected: &RuaType) {
+    match convert_type(src) {
+        Ok(ty) => assert_eq!(&ty, expected, "converting {:?}", src),
+        Err(err) => panic!("failed to convert {:?}: {}", src, err),
+    }
+}
+
+/// Asserts that the type fails to convert, and returns the error so that its
+/// message can be checked too.
+///
+/// # Panics
+///
+/// Panics if the source is not a valid type or converts successfully.
+#[track_caller]
+pub fn assert_fails(src: &str) -> ConversionError {
+    match convert_type(src) {
+        Ok(ty) => panic!("expected {:?} to fail, got {:?}", src, ty),
+        Err(err) => err,
+    }
+}
+
+/// Asserts that the type survives a round trip, i.e. rendering its model
+/// with [RuaRustRenderer] and converting the result again gives the same
+/// model. Only the types whose models keep everything that is rendered can
+/// round-trip, e.g. not `&'a T`, whose lifetime is not rendered.
+///
+/// # Panics
+///
+/// Panics if the source is not a valid type, fails to convert or the models
+/// differ.
+#[track_caller]
+pub fn assert_round_trips(src: &str) {
+    let ty = convert_type(src)
+        .unwrap_or_else(|err| panic!("failed to convert {:?}: {}", src, err));
+    let rendered = RuaRustRenderer.render(&ty);
+    match convert_type(&rendered) {
+        Ok(again) => assert_eq!(
+            again, ty,
+            "{:?} was rendered as {:?}, which converts differently",
+            src, rendered
+        ),
+        Err(err) => panic!(
+            "{:?} was rendered as {:?}, which fails to convert: {}",
+            src, rendered, err
+        ),
+    }
+}
+
+/// Returns representative types as Rust source along with their expected
+/// models, covering every [RuaType] variant that conversion produces. It
+/// never produces [RuaType::Struct] and [RuaType::Enum], which name types
+/// as [RuaType::Custom]. Platform crates can render all of them to check
+/// their renderers handle every variant.
+pub fn representative_types() -> Vec<(&'static str, RuaType)> {
+    vec![
+        ("i8", RuaType::I8),
+        ("i16", RuaType::I16),
+        ("i32", RuaType::I32),
+        ("i64", RuaType::I64),
+        ("i128", RuaType::I128),
+        ("isize", RuaType::Isize),
+        ("u8", RuaType::U8),
+        ("u16", RuaType::U16),
+        ("u32", RuaType::U32),
+        ("u64", RuaType::U64),
+        ("u128", RuaType::U128),
+        ("usize", RuaType::Usize),
+        ("f32", RuaType::F32),
+        ("f64", RuaType::F64),
+        ("bool", RuaType::Bool),
+        ("char", RuaType::Char),
+        ("str", RuaType::Str),
+        ("String", RuaType::String),
+        ("()", RuaType::Unit),
+        ("[u8]", RuaType::Slice(RuaSlice::new(RuaType::U8))),
+        (
+            "[f32; 4]",
+            RuaType::Array(RuaArray::new(RuaType::F32, RuaArrayLen::Num(4))),
+        ),
+        (
+            "(i32, bool)",
+            RuaType::Tuple(RuaTuple {
+                tys: vec![RuaType::I32, RuaType::Bool],
+            }),
+        ),
+        (
+            "&mut str",
+            RuaType::Reference(RuaReference::new(true, RuaType::Str)),
+        ),
+        (
+            "*const u8",
+            RuaType::Pointer(RuaPointer {
+                is_const: true,
+                is_non_null: false,
+                ty: Box::new(RuaType::U8),
+            }),
+        ),
+        (
+            "NonZeroU32",
+            RuaType::NonZero(RuaNonZero {
+                ty: Box::new(RuaType::U32),
+            }),
+        ),
+        (
+            "fn(u8) -> bool",
+            RuaType::Fn(RuaFn::Bare(RuaBareFn {
+                params: vec![RuaType::U8],
+                ret: Box::new(RuaType::Bool),
+            })),
+        ),
+        (
+            "Point",
+            RuaType::Custom(RuaCustom::new(RuaName::new(
+                "Point",
+                RuaCase::PascalCase,
+            ))),
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn representative_types_convert_and_round_trip() {
+        for (src, expected) in representative_types() {
+            assert_converts(src, &expected);
+            assert_round_trips(src);
+        }
+    }
+
+    #[test]
+    fn qualified_path_fails() {
+        let err = assert_fails("<T as Trait>::Item");
+        assert!(err.to_string().contains("unsupported qualified path"));
+    }
+}